@@ -0,0 +1,66 @@
+use crate::opcodes::{self, is_push};
+
+/// Bitmap of the positions in a bytecode that hold a valid `JUMPDEST`.
+///
+/// A `0x5b` byte is only a jump destination when it is an instruction, not
+/// when it is part of the immediate data of a preceding PUSH.
+#[derive(Debug, Clone, Default)]
+pub struct JumpTable {
+    bits: Vec<u64>,
+}
+
+impl JumpTable {
+    pub fn analyze(bytecode: &[u8]) -> Self {
+        let mut bits = vec![0u64; bytecode.len().div_ceil(64)];
+        let mut pc = 0;
+
+        while pc < bytecode.len() {
+            let opcode = bytecode[pc];
+            if opcode == opcodes::JUMPDEST {
+                bits[pc / 64] |= 1 << (pc % 64);
+            } else if is_push(opcode) {
+                pc += (opcode - opcodes::PUSH0) as usize;
+            }
+            pc += 1;
+        }
+
+        Self { bits }
+    }
+
+    pub fn is_valid(&self, pc: usize) -> bool {
+        self.bits
+            .get(pc / 64)
+            .is_some_and(|word| word & (1 << (pc % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jumpdest_detected() {
+        // PUSH1 0x03, JUMP, JUMPDEST
+        let table = JumpTable::analyze(&[0x60, 0x03, 0x56, 0x5b]);
+        assert!(table.is_valid(3));
+        assert!(!table.is_valid(0));
+        assert!(!table.is_valid(100));
+    }
+
+    #[test]
+    fn test_jumpdest_inside_push_data() {
+        // PUSH2 0x5b5b, JUMPDEST
+        let table = JumpTable::analyze(&[0x61, 0x5b, 0x5b, 0x5b]);
+        assert!(!table.is_valid(1));
+        assert!(!table.is_valid(2));
+        assert!(table.is_valid(3));
+    }
+
+    #[test]
+    fn test_truncated_push() {
+        // PUSH32 with only two bytes of data
+        let table = JumpTable::analyze(&[0x7f, 0x5b, 0x5b]);
+        assert!(!table.is_valid(1));
+        assert!(!table.is_valid(2));
+    }
+}
@@ -0,0 +1,76 @@
+use primitive_types::{H160, U256};
+
+pub type Address = H160;
+
+/// The message being executed: who sent it, to whom, and with what input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallContext {
+    /// Immediate sender of the message (`msg.sender`).
+    pub caller: Address,
+    /// Sender of the original transaction (`tx.origin`).
+    pub origin: Address,
+    /// Address of the account whose code is running.
+    pub address: Address,
+    /// Gas price of the original transaction (`tx.gasprice`).
+    pub gas_price: U256,
+    /// Wei sent along with the message (`msg.value`).
+    pub value: U256,
+    /// Input data (`msg.data`).
+    pub data: Vec<u8>,
+    pub gas_limit: u64,
+    /// Accounts and slots the transaction declares up front (EIP-2930), so
+    /// they start out warm. Only read for the outermost message.
+    pub access_list: Vec<AccessListItem>,
+}
+
+impl Default for CallContext {
+    fn default() -> Self {
+        Self {
+            caller: Address::zero(),
+            origin: Address::zero(),
+            address: Address::zero(),
+            gas_price: U256::zero(),
+            value: U256::zero(),
+            data: Vec::new(),
+            gas_limit: u64::MAX,
+            access_list: Vec::new(),
+        }
+    }
+}
+
+/// One entry of an EIP-2930 access list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<U256>,
+}
+
+/// The block the message is executed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEnv {
+    pub number: U256,
+    pub timestamp: U256,
+    /// Beneficiary of the block (`block.coinbase`).
+    pub coinbase: Address,
+    /// Beacon chain randomness, which replaced DIFFICULTY at the Merge.
+    pub prevrandao: U256,
+    pub gas_limit: u64,
+    pub chain_id: u64,
+    pub basefee: U256,
+    pub blob_basefee: U256,
+}
+
+impl Default for BlockEnv {
+    fn default() -> Self {
+        Self {
+            number: U256::zero(),
+            timestamp: U256::zero(),
+            coinbase: Address::zero(),
+            prevrandao: U256::zero(),
+            gas_limit: 30_000_000,
+            chain_id: 1,
+            basefee: U256::zero(),
+            blob_basefee: U256::one(),
+        }
+    }
+}
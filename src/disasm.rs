@@ -0,0 +1,194 @@
+//! Turns bytecode back into instructions, for reading code rather than
+//! running it.
+
+use crate::opcodes;
+use std::fmt;
+
+/// Mnemonic given to bytes that are not an assigned opcode.
+const UNKNOWN: &str = "INVALID";
+
+/// A single decoded instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Offset of the opcode in the code.
+    pub pc: usize,
+    pub opcode: u8,
+    /// Name of the opcode, or `INVALID` for an unassigned byte.
+    pub mnemonic: &'static str,
+    /// Data of a PUSH. Shorter than the PUSH size when the code ends first.
+    pub immediate: Vec<u8>,
+}
+
+impl Instruction {
+    /// Whether the byte is an assigned opcode.
+    pub fn is_known(&self) -> bool {
+        opcodes::info(self.opcode).is_some()
+    }
+
+    /// Whether the code ended before the PUSH data did, which the
+    /// interpreter halts on with [`EvmError::InvalidPush`].
+    ///
+    /// [`EvmError::InvalidPush`]: crate::error::EvmError::InvalidPush
+    pub fn is_truncated(&self) -> bool {
+        opcodes::info(self.opcode).is_some_and(|info| self.immediate.len() < info.immediate_size)
+    }
+
+    /// Offset of the instruction that follows.
+    pub fn next_pc(&self) -> usize {
+        self.pc + 1 + self.immediate.len()
+    }
+}
+
+/// Writes the instruction the way test comments spell it, e.g.
+/// `PUSH1 0x01` or `ADD`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mnemonic)?;
+        if !self.immediate.is_empty() {
+            write!(f, " 0x{}", hex::encode(&self.immediate))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `code` into instructions, stepping over PUSH data the way the
+/// interpreter does.
+pub fn disassemble(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let (mnemonic, immediate_size) = match opcodes::info(opcode) {
+            Some(info) => (info.mnemonic, info.immediate_size),
+            None => (UNKNOWN, 0),
+        };
+        let end = (pc + 1 + immediate_size).min(code.len());
+        let instruction = Instruction {
+            pc,
+            opcode,
+            mnemonic,
+            immediate: code[pc + 1..end].to_vec(),
+        };
+        pc = instruction.next_pc();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// A listing of `code` with one instruction per line, prefixed by its
+/// offset. Unassigned bytes and PUSH data cut short by the end of the code
+/// are called out in a trailing comment.
+pub fn listing(code: &[u8]) -> String {
+    let mut listing = String::new();
+    for instruction in disassemble(code) {
+        let mut line = format!("{:04x}: {instruction}", instruction.pc);
+        if !instruction.is_known() {
+            line.push_str(&format!(" ; unknown opcode 0x{:02x}", instruction.opcode));
+        } else if instruction.is_truncated() {
+            line.push_str(" ; truncated");
+        }
+        listing.push_str(&line);
+        listing.push('\n');
+    }
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    fn mnemonics(code: &str) -> String {
+        let instructions = disassemble(&hex_to_bytes(code).unwrap());
+        let words: Vec<_> = instructions.iter().map(ToString::to_string).collect();
+        words.join(", ")
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let instructions = disassemble(&hex_to_bytes("0x6001600201").unwrap());
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction {
+                    pc: 0,
+                    opcode: opcodes::PUSH1,
+                    mnemonic: "PUSH1",
+                    immediate: vec![0x01],
+                },
+                Instruction {
+                    pc: 2,
+                    opcode: opcodes::PUSH1,
+                    mnemonic: "PUSH1",
+                    immediate: vec![0x02],
+                },
+                Instruction {
+                    pc: 4,
+                    opcode: opcodes::ADD,
+                    mnemonic: "ADD",
+                    immediate: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_test_comments() {
+        assert_eq!(mnemonics("0x6001600201"), "PUSH1 0x01, PUSH1 0x02, ADD");
+        assert_eq!(
+            mnemonics("0x60ef60005360016000f3"),
+            "PUSH1 0xef, PUSH1 0x00, MSTORE8, PUSH1 0x01, PUSH1 0x00, RETURN"
+        );
+        // Bytes inside PUSH data are not instructions.
+        assert_eq!(mnemonics("0x615b5b5b"), "PUSH2 0x5b5b, JUMPDEST");
+        assert_eq!(mnemonics("0x5f"), "PUSH0");
+    }
+
+    #[test]
+    fn test_truncated_push() {
+        let instructions = disassemble(&[opcodes::PUSH32, 0x5b, 0x5b]);
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].immediate, vec![0x5b, 0x5b]);
+        assert!(instructions[0].is_truncated());
+        assert_eq!(instructions[0].next_pc(), 3);
+
+        let instructions = disassemble(&[opcodes::PUSH1]);
+        assert!(instructions[0].immediate.is_empty());
+        assert!(instructions[0].is_truncated());
+        assert!(!disassemble(&[opcodes::PUSH1, 0x00])[0].is_truncated());
+    }
+
+    #[test]
+    fn test_unknown_bytes() {
+        let instructions = disassemble(&[0x0c, opcodes::INVALID]);
+        assert_eq!(instructions[0].mnemonic, "INVALID");
+        assert!(!instructions[0].is_known());
+        // 0xfe is the designated invalid instruction, not an unknown byte.
+        assert_eq!(instructions[1].mnemonic, "INVALID");
+        assert!(instructions[1].is_known());
+    }
+
+    #[test]
+    fn test_assigned_opcodes_are_named() {
+        // Assigned opcodes keep their names, whether or not the
+        // interpreter runs them yet.
+        assert_eq!(mnemonics("0x40"), "BLOCKHASH");
+        assert_eq!(mnemonics("0x49"), "BLOBHASH");
+        assert_eq!(listing(&[0x40, 0x49]), "0000: BLOCKHASH\n0001: BLOBHASH\n");
+    }
+
+    #[test]
+    fn test_listing() {
+        let code = hex_to_bytes("0x6001600201fe0c61ff").unwrap();
+        assert_eq!(
+            listing(&code),
+            "0000: PUSH1 0x01\n\
+             0002: PUSH1 0x02\n\
+             0004: ADD\n\
+             0005: INVALID\n\
+             0006: INVALID ; unknown opcode 0x0c\n\
+             0007: PUSH2 0xff ; truncated\n"
+        );
+        assert_eq!(listing(&[]), "");
+    }
+}
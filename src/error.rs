@@ -1,6 +1,7 @@
+use primitive_types::U256;
 use thiserror::Error;
 
-#[derive(Debug, Error, PartialEq, Eq)]
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum EvmError {
     #[error("Stack overflow: maximum stack size is 1024")]
     StackOverflow,
@@ -20,6 +21,39 @@ pub enum EvmError {
     #[error("Invalid SWAP: not enough values on stack")]
     InvalidSwap,
 
+    #[error("Invalid jump destination: {0}")]
+    InvalidJump(U256),
+
+    #[error("Out of gas")]
+    OutOfGas,
+
+    #[error("State modification attempted in a static call")]
+    StateChangeInStaticContext,
+
+    #[error("Insufficient balance for value transfer")]
+    InsufficientBalance,
+
+    #[error("Initcode size exceeds the limit of 49152 bytes")]
+    InitcodeSizeExceeded,
+
+    #[error("Contract code size exceeds the limit of 24576 bytes")]
+    CodeSizeExceeded,
+
+    #[error("Contract code must not start with 0xEF")]
+    InvalidCodePrefix,
+
+    #[error("Contract address collision")]
+    CreateCollision,
+
+    #[error("Nonce overflow")]
+    NonceOverflow,
+
+    #[error("Return data access out of bounds")]
+    ReturnDataOutOfBounds,
+
+    #[error("Precompile failed: {0}")]
+    PrecompileFailure(String),
+
     #[error("Memory access out of bounds")]
     MemoryOutOfBounds,
 
@@ -1,51 +1,510 @@
+use crate::context::{Address, BlockEnv, CallContext};
 use crate::error::{EvmError, Result};
+use crate::frame::{
+    CALL_DEPTH_LIMIT, CallInputs, CallKind, CreateInputs, CreateScheme, Frame, MAX_CODE_SIZE,
+    MAX_INITCODE_SIZE,
+};
+use crate::gas::{self, Gas};
 use crate::handlers::{
-    handle_arithmetic, handle_dup, handle_push, handle_sload, handle_sstore, handle_swap,
+    handle_address, handle_arithmetic, handle_balance, handle_block_info, handle_call,
+    handle_calldatacopy, handle_calldataload, handle_calldatasize, handle_caller, handle_callvalue,
+    handle_codecopy, handle_codesize, handle_create, handle_dup, handle_extcodecopy,
+    handle_extcodehash, handle_extcodesize, handle_gasprice, handle_jump, handle_jumpi,
+    handle_keccak256, handle_log, handle_mcopy, handle_mload, handle_msize, handle_mstore,
+    handle_mstore8, handle_origin, handle_pc, handle_push, handle_return, handle_returndatacopy,
+    handle_returndatasize, handle_selfdestruct, handle_sload, handle_sstore, handle_swap,
+    handle_tload, handle_tstore,
 };
+use crate::journal::Checkpoint;
+use crate::log::Log;
 use crate::memory::Memory;
-use crate::opcodes::{self, is_dup, is_push, is_swap};
+use crate::opcodes::{self, is_dup, is_log, is_push, is_swap};
+use crate::precompiles::Precompiles;
+use crate::result::ExecutionResult;
+use crate::spec::SpecId;
 use crate::stack::Stack;
-use crate::storage::Storage;
-use primitive_types::U256;
+use crate::state::WorldState;
+use crate::utils::{address_to_u256, create_address, create2_address, keccak256};
+use primitive_types::{H256, U256};
 
 #[derive(Debug, Default)]
 pub struct Evm {
-    stack: Stack,
-    storage: Storage,
-    memory: Memory,
-    pc: usize,
-    running: bool,
+    state: WorldState,
+    block: BlockEnv,
+    spec: SpecId,
+    precompiles: Precompiles,
+    /// Suspended callers of the frame that is currently running.
+    frames: Vec<Frame>,
+    /// Root frame of the last execution, kept for inspection.
+    frame: Frame,
 }
 
 impl Evm {
     pub fn new() -> Self {
         Self {
-            stack: Stack::new(),
-            storage: Storage::new(),
-            memory: Memory::new(),
-            pc: 0,
-            running: true,
+            state: WorldState::new(),
+            block: BlockEnv::default(),
+            spec: SpecId::default(),
+            precompiles: Precompiles::default(),
+            frames: Vec::new(),
+            frame: Frame::default(),
+        }
+    }
+
+    /// Executes `bytecode` with the whole block gas limit, which also caps
+    /// how far memory can grow.
+    pub fn execute(&mut self, bytecode: &[u8]) -> ExecutionResult {
+        self.execute_with_gas(bytecode, self.block.gas_limit)
+    }
+
+    /// Executes `bytecode`, halting with [`EvmError::OutOfGas`] once
+    /// `gas_limit` is exhausted. Any exceptional halt consumes all gas.
+    pub fn execute_with_gas(&mut self, bytecode: &[u8], gas_limit: u64) -> ExecutionResult {
+        let context = CallContext {
+            gas_limit,
+            ..Default::default()
+        };
+        self.execute_with_context(bytecode, context)
+    }
+
+    /// Executes `bytecode` as the message described by `context`, which
+    /// supplies the caller, callee, value, calldata and gas limit.
+    pub fn execute_with_context(
+        &mut self,
+        bytecode: &[u8],
+        context: CallContext,
+    ) -> ExecutionResult {
+        let checkpoint = self.begin_execution(&context);
+        let mut frame = Frame::new(bytecode.to_vec(), context, false, 0);
+        frame.checkpoint = checkpoint;
+        self.run(frame)
+    }
+
+    /// Sends the message described by `context` to the code stored at
+    /// `context.address`, moving `context.value` from the caller first.
+    pub fn call(&mut self, context: CallContext) -> ExecutionResult {
+        if self.state.balance(&context.caller) < context.value {
+            return ExecutionResult::Halt {
+                reason: EvmError::InsufficientBalance,
+            };
+        }
+        let checkpoint = self.begin_execution(&context);
+        self.transfer(context.caller, context.address, context.value);
+
+        let code = self.state.code(&context.address).to_vec();
+        let address = context.address;
+        let mut frame = Frame::new(code, context, false, 0);
+        frame.checkpoint = checkpoint;
+        if self.precompiles.contains(&address) {
+            frame.precompile = Some(address);
+        }
+        self.run(frame)
+    }
+
+    /// Deploys a contract by running `init_code` with `value` attached and
+    /// the block gas limit. Returns the address of the new contract along
+    /// with the result.
+    pub fn deploy(&mut self, init_code: &[u8], value: U256) -> (Address, ExecutionResult) {
+        let context = CallContext {
+            value,
+            gas_limit: self.block.gas_limit,
+            ..Default::default()
+        };
+        self.deploy_with_context(init_code, context)
+    }
+
+    /// Deploys a contract created by `context.caller`, which pays
+    /// `context.value` into it. `context.address` and `context.data` are
+    /// ignored.
+    pub fn deploy_with_context(
+        &mut self,
+        init_code: &[u8],
+        mut context: CallContext,
+    ) -> (Address, ExecutionResult) {
+        let address = self.new_contract_address(CreateScheme::Create, context.caller, init_code);
+
+        let reason = if self.spec >= SpecId::Shanghai && init_code.len() > MAX_INITCODE_SIZE {
+            EvmError::InitcodeSizeExceeded
+        } else if self.state.balance(&context.caller) < context.value {
+            EvmError::InsufficientBalance
+        } else if self.state.nonce(&context.caller) == u64::MAX {
+            EvmError::NonceOverflow
+        } else {
+            context.address = address;
+            self.begin_execution(&context);
+            match self.prepare_create(address, context, init_code.to_vec(), 0) {
+                Ok(frame) => return (address, self.run(frame)),
+                Err(reason) => reason,
+            }
+        };
+        (address, ExecutionResult::Halt { reason })
+    }
+
+    /// Forgets the logs of the previous execution, warms the accounts and
+    /// slots every transaction starts with (EIP-2929, EIP-2930, EIP-3651)
+    /// and marks the state the execution starts from.
+    fn begin_execution(&mut self, context: &CallContext) -> Checkpoint {
+        // Whatever was changed through `state_mut` since the last execution
+        // is where this transaction starts from.
+        self.state.end_transaction();
+        self.state.clear_logs();
+
+        self.state.warm_address(context.caller);
+        self.state.warm_address(context.address);
+        if self.spec >= SpecId::Shanghai {
+            self.state.warm_address(self.block.coinbase);
+        }
+        for address in self.precompiles.addresses() {
+            self.state.warm_address(*address);
+        }
+        for item in &context.access_list {
+            self.state.warm_address(item.address);
+            for key in &item.storage_keys {
+                self.state.warm_slot(item.address, *key);
+            }
+        }
+
+        self.state.checkpoint()
+    }
+
+    /// Drives the call stack until the root frame finishes.
+    fn run(&mut self, root: Frame) -> ExecutionResult {
+        self.frames.clear();
+
+        let mut frame = root;
+        loop {
+            let result = self.run_frame(&mut frame);
+
+            if let Some(inputs) = frame.pending_call.take() {
+                if let Some(child) = self.enter_call(&mut frame, inputs) {
+                    self.frames.push(std::mem::replace(&mut frame, child));
+                }
+                continue;
+            }
+            if let Some(inputs) = frame.pending_create.take() {
+                if let Some(child) = self.enter_create(&mut frame, inputs) {
+                    self.frames.push(std::mem::replace(&mut frame, child));
+                }
+                continue;
+            }
+
+            let result = self.exit_frame(&mut frame, result);
+            match self.frames.pop() {
+                Some(parent) => {
+                    let child = std::mem::replace(&mut frame, parent);
+                    self.return_to_caller(&mut frame, child, &result);
+                }
+                None => {
+                    self.state.end_transaction();
+                    self.frame = frame;
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Runs `frame` until it stops or requests a call.
+    fn run_frame(&mut self, frame: &mut Frame) -> Result<()> {
+        if let Some(address) = frame.precompile {
+            return self.run_precompile(frame, address);
+        }
+
+        while frame.running && frame.pc < frame.code.len() {
+            let opcode = frame.code[frame.pc];
+            frame.pc += 1;
+
+            if !self.spec.is_opcode_enabled(opcode) {
+                return Err(EvmError::InvalidOpcode(opcode));
+            }
+            frame.gas.charge(gas::static_cost(self.spec, opcode))?;
+            self.execute_opcode(frame, opcode)?;
+
+            if frame.pending_call.is_some() || frame.pending_create.is_some() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the precompile at `address` on the frame's calldata, charging
+    /// its gas and keeping its output.
+    fn run_precompile(&self, frame: &mut Frame, address: Address) -> Result<()> {
+        let Some(precompile) = self.precompiles.get(&address) else {
+            return Ok(());
+        };
+        let output = precompile.run(&frame.context.data, frame.gas.remaining())?;
+        frame.gas.charge(output.gas_used)?;
+        frame.output = output.output;
+        Ok(())
+    }
+
+    /// Turns the way a frame stopped into its result, consuming all gas on
+    /// an exceptional halt and undoing the frame's state changes on failure.
+    fn exit_frame(&mut self, frame: &mut Frame, result: Result<()>) -> ExecutionResult {
+        let result = match result.and_then(|()| self.deposit_code(frame)) {
+            Ok(()) => {
+                let output = std::mem::take(&mut frame.output);
+                let gas_used = frame.gas.used();
+                if frame.reverted {
+                    ExecutionResult::Revert { output, gas_used }
+                } else {
+                    // Logs and refunds are only reported once, for the whole
+                    // execution.
+                    let (logs, gas_refunded) = if frame.depth == 0 {
+                        let refund = gas::capped_refund(self.spec, self.state.refund(), gas_used);
+                        (self.state.logs().to_vec(), refund)
+                    } else {
+                        (Vec::new(), 0)
+                    };
+                    ExecutionResult::Success {
+                        output,
+                        gas_used: gas_used - gas_refunded,
+                        gas_refunded,
+                        logs,
+                    }
+                }
+            }
+            Err(reason) => {
+                frame.gas.consume_all();
+                ExecutionResult::Halt { reason }
+            }
+        };
+
+        if !result.is_success() {
+            self.state.revert_to(frame.checkpoint);
+        }
+        result
+    }
+
+    /// Prepares the child frame for a call, or fails the call straight away
+    /// (pushing 0 and refunding the forwarded gas) when it cannot start.
+    fn enter_call(&mut self, parent: &mut Frame, inputs: CallInputs) -> Option<Frame> {
+        let CallInputs {
+            kind,
+            code_address,
+            context,
+            transfer_value,
+            is_static,
+            return_offset,
+            return_size,
+        } = inputs;
+
+        let too_deep = parent.depth >= CALL_DEPTH_LIMIT;
+        let too_poor = self.state.balance(&context.caller) < transfer_value;
+        if too_deep || too_poor {
+            parent.gas.return_unused(context.gas_limit);
+            parent.return_data.clear();
+            // Pushing can't overflow: the call's own arguments were just popped.
+            let _ = parent.stack.push(U256::zero());
+            return None;
+        }
+
+        let checkpoint = self.state.checkpoint();
+        if kind != CallKind::DelegateCall {
+            self.transfer(context.caller, context.address, transfer_value);
+        }
+
+        let code = self.state.code(&code_address).to_vec();
+        let mut child = Frame::new(
+            code,
+            context,
+            parent.is_static || is_static,
+            parent.depth + 1,
+        );
+        child.checkpoint = checkpoint;
+        if self.precompiles.contains(&code_address) {
+            child.precompile = Some(code_address);
+        }
+        child.return_offset = return_offset;
+        child.return_size = return_size;
+        Some(child)
+    }
+
+    /// Prepares the child frame running the initcode, or fails the creation
+    /// straight away (pushing 0) when it cannot start.
+    fn enter_create(&mut self, parent: &mut Frame, inputs: CreateInputs) -> Option<Frame> {
+        let CreateInputs {
+            scheme,
+            caller,
+            value,
+            init_code,
+            gas_limit,
+        } = inputs;
+        parent.return_data.clear();
+
+        let too_deep = parent.depth >= CALL_DEPTH_LIMIT;
+        let too_poor = self.state.balance(&caller) < value;
+        let nonce_overflow = self.state.nonce(&caller) == u64::MAX;
+        if too_deep || too_poor || nonce_overflow {
+            parent.gas.return_unused(gas_limit);
+            // Pushing can't overflow: the creation's own arguments were just popped.
+            let _ = parent.stack.push(U256::zero());
+            return None;
+        }
+
+        let address = self.new_contract_address(scheme, caller, &init_code);
+        let context = CallContext {
+            caller,
+            origin: parent.context.origin,
+            gas_price: parent.context.gas_price,
+            value,
+            gas_limit,
+            ..Default::default()
+        };
+        match self.prepare_create(address, context, init_code, parent.depth + 1) {
+            Ok(child) => Some(child),
+            Err(_) => {
+                // A collision keeps the forwarded gas.
+                let _ = parent.stack.push(U256::zero());
+                None
+            }
+        }
+    }
+
+    fn new_contract_address(
+        &self,
+        scheme: CreateScheme,
+        caller: Address,
+        init_code: &[u8],
+    ) -> Address {
+        match scheme {
+            CreateScheme::Create => create_address(caller, self.state.nonce(&caller)),
+            CreateScheme::Create2 { salt } => {
+                create2_address(caller, salt, H256::from(keccak256(init_code)))
+            }
         }
     }
 
-    pub fn execute(&mut self, bytecode: &[u8]) -> Result<()> {
-        self.pc = 0;
-        self.running = true;
+    /// Bumps the creator's nonce and, unless `address` is already taken,
+    /// funds the new account and builds the frame running its initcode. The
+    /// nonce bump survives a failed creation; everything after it does not.
+    fn prepare_create(
+        &mut self,
+        address: Address,
+        mut context: CallContext,
+        init_code: Vec<u8>,
+        depth: usize,
+    ) -> Result<Frame> {
+        let nonce = self.state.nonce(&context.caller);
+        self.state.set_nonce(context.caller, nonce + 1);
+        self.state.warm_address(address);
+        let checkpoint = self.state.checkpoint();
+
+        if self
+            .state
+            .account(&address)
+            .is_some_and(|account| account.nonce != 0 || !account.code.is_empty())
+        {
+            return Err(EvmError::CreateCollision);
+        }
+
+        // New contracts start at nonce 1 since EIP-161.
+        if self.spec >= SpecId::SpuriousDragon {
+            self.state.set_nonce(address, 1);
+        }
+        self.state.mark_created(address);
+        self.transfer(context.caller, address, context.value);
+
+        context.address = address;
+        context.data = Vec::new();
+        let mut frame = Frame::new(init_code, context, false, depth);
+        frame.created_address = Some(address);
+        frame.checkpoint = checkpoint;
+        Ok(frame)
+    }
 
-        while self.running && self.pc < bytecode.len() {
-            let opcode = bytecode[self.pc];
-            self.pc += 1;
+    /// Stores the code returned by a successful initcode run, charging for
+    /// every byte of it.
+    fn deposit_code(&mut self, frame: &mut Frame) -> Result<()> {
+        let Some(address) = frame.created_address else {
+            return Ok(());
+        };
+        if frame.reverted {
+            return Ok(());
+        }
 
-            self.execute_opcode(opcode, bytecode)?;
+        let code = &frame.output;
+        if self.spec >= SpecId::SpuriousDragon && code.len() > MAX_CODE_SIZE {
+            return Err(EvmError::CodeSizeExceeded);
+        }
+        if self.spec >= SpecId::London && code.first() == Some(&0xef) {
+            // Reserved for EOF (EIP-3541).
+            return Err(EvmError::InvalidCodePrefix);
         }
+        let cost = gas::code_deposit_cost(code.len());
+        if self.spec < SpecId::Homestead && cost > frame.gas.remaining() {
+            // Frontier kept the new account, just without any code.
+            return Ok(());
+        }
+        frame.gas.charge(cost)?;
 
+        self.state.set_code(address, code.clone());
         Ok(())
     }
 
-    fn execute_opcode(&mut self, opcode: u8, bytecode: &[u8]) -> Result<()> {
+    /// Hands a finished child's result back to its caller.
+    fn return_to_caller(&mut self, parent: &mut Frame, child: Frame, result: &ExecutionResult) {
+        if !result.is_halt() {
+            parent.gas.return_unused(child.gas.remaining());
+        }
+
+        if let Some(address) = child.created_address {
+            // A successful creation leaves no return data behind.
+            let (return_data, pushed) = if result.is_success() {
+                (Vec::new(), address_to_u256(address))
+            } else {
+                (result.output().to_vec(), U256::zero())
+            };
+            parent.return_data = return_data;
+            let _ = parent.stack.push(pushed);
+            return;
+        }
+
+        let output = result.output();
+        let copied = output.len().min(child.return_size);
+        // The return range was expanded when the call was made.
+        let _ = parent
+            .memory
+            .write_bytes(child.return_offset, &output[..copied]);
+        parent.return_data = output.to_vec();
+
+        let success = U256::from(u8::from(result.is_success()));
+        let _ = parent.stack.push(success);
+    }
+
+    fn transfer(&mut self, from: Address, to: Address, value: U256) {
+        if value.is_zero() || from == to {
+            return;
+        }
+        let from_balance = self.state.balance(&from);
+        self.state.set_balance(from, from_balance - value);
+        let to_balance = self.state.balance(&to);
+        self.state.set_balance(to, to_balance + value);
+    }
+
+    fn execute_opcode(&mut self, frame: &mut Frame, opcode: u8) -> Result<()> {
+        let Frame {
+            stack,
+            memory,
+            context,
+            code,
+            jump_table,
+            gas,
+            return_data,
+            pc,
+            is_static,
+            ..
+        } = frame;
+
         match opcode {
             opcodes::STOP => {
-                self.running = false;
+                frame.running = false;
+            }
+
+            opcodes::EXP => {
+                gas.charge(gas::exp_cost(self.spec, stack.peek(1)?))?;
+                handle_arithmetic(opcode, stack)?;
             }
 
             // Arithmetic & Comparison & Bitwise Operations
@@ -58,7 +517,6 @@ fn execute_opcode(&mut self, opcode: u8, bytecode: &[u8]) -> Result<()> {
             | opcodes::SMOD
             | opcodes::ADDMOD
             | opcodes::MULMOD
-            | opcodes::EXP
             | opcodes::SIGNEXTEND
             | opcodes::LT
             | opcodes::GT
@@ -74,29 +532,194 @@ fn execute_opcode(&mut self, opcode: u8, bytecode: &[u8]) -> Result<()> {
             | opcodes::SHL
             | opcodes::SHR
             | opcodes::SAR => {
-                handle_arithmetic(opcode, &mut self.stack)?;
+                handle_arithmetic(opcode, stack)?;
+            }
+
+            // Hashing
+            opcodes::KECCAK256 => {
+                handle_keccak256(memory, stack, gas)?;
+            }
+
+            // Environmental Information
+            opcodes::ADDRESS => {
+                handle_address(context, stack)?;
+            }
+            opcodes::BALANCE => {
+                handle_balance(self.spec, &mut self.state, stack, gas)?;
+            }
+            opcodes::ORIGIN => {
+                handle_origin(context, stack)?;
+            }
+            opcodes::CALLER => {
+                handle_caller(context, stack)?;
+            }
+            opcodes::CALLVALUE => {
+                handle_callvalue(context, stack)?;
+            }
+            opcodes::CALLDATALOAD => {
+                handle_calldataload(context, stack)?;
+            }
+            opcodes::CALLDATASIZE => {
+                handle_calldatasize(context, stack)?;
+            }
+            opcodes::CALLDATACOPY => {
+                handle_calldatacopy(context, memory, stack, gas)?;
+            }
+            opcodes::CODESIZE => {
+                handle_codesize(code, stack)?;
+            }
+            opcodes::CODECOPY => {
+                handle_codecopy(code, memory, stack, gas)?;
+            }
+            opcodes::GASPRICE => {
+                handle_gasprice(context, stack)?;
+            }
+            opcodes::EXTCODESIZE => {
+                handle_extcodesize(self.spec, &mut self.state, stack, gas)?;
+            }
+            opcodes::EXTCODECOPY => {
+                handle_extcodecopy(self.spec, &mut self.state, memory, stack, gas)?;
+            }
+            opcodes::RETURNDATASIZE => {
+                handle_returndatasize(return_data, stack)?;
+            }
+            opcodes::RETURNDATACOPY => {
+                handle_returndatacopy(return_data, memory, stack, gas)?;
+            }
+            opcodes::EXTCODEHASH => {
+                handle_extcodehash(self.spec, &mut self.state, stack, gas)?;
+            }
+
+            // Block Information
+            opcodes::COINBASE
+            | opcodes::TIMESTAMP
+            | opcodes::NUMBER
+            | opcodes::PREVRANDAO
+            | opcodes::GASLIMIT
+            | opcodes::CHAINID
+            | opcodes::BASEFEE
+            | opcodes::BLOBBASEFEE => {
+                handle_block_info(opcode, &self.block, stack)?;
+            }
+            opcodes::SELFBALANCE => {
+                stack.push(self.state.balance(&context.address))?;
+            }
+
+            // Memory Operations
+            opcodes::MLOAD => {
+                handle_mload(memory, stack, gas)?;
+            }
+            opcodes::MSTORE => {
+                handle_mstore(memory, stack, gas)?;
+            }
+            opcodes::MSTORE8 => {
+                handle_mstore8(memory, stack, gas)?;
+            }
+            opcodes::MSIZE => {
+                handle_msize(memory, stack)?;
+            }
+            opcodes::MCOPY => {
+                handle_mcopy(memory, stack, gas)?;
             }
 
             // Storage Operations
             opcodes::SLOAD => {
-                handle_sload(&self.storage, &mut self.stack)?;
+                handle_sload(self.spec, &mut self.state, context.address, stack, gas)?;
             }
             opcodes::SSTORE => {
-                handle_sstore(&mut self.storage, &mut self.stack)?;
+                if *is_static {
+                    return Err(EvmError::StateChangeInStaticContext);
+                }
+                handle_sstore(self.spec, &mut self.state, context.address, stack, gas)?;
+            }
+            opcodes::TLOAD => {
+                handle_tload(&self.state, &context.address, stack)?;
+            }
+            opcodes::TSTORE => {
+                if *is_static {
+                    return Err(EvmError::StateChangeInStaticContext);
+                }
+                handle_tstore(&mut self.state, context.address, stack)?;
+            }
+
+            // Control Flow Operations
+            opcodes::JUMP => {
+                handle_jump(stack, jump_table, pc)?;
+            }
+            opcodes::JUMPI => {
+                handle_jumpi(stack, jump_table, pc)?;
+            }
+            opcodes::PC => {
+                handle_pc(stack, *pc)?;
+            }
+            opcodes::JUMPDEST => {}
+            opcodes::GAS => {
+                stack.push(U256::from(gas.remaining()))?;
+            }
+
+            // Logging Operations
+            _ if is_log(opcode) => {
+                if *is_static {
+                    return Err(EvmError::StateChangeInStaticContext);
+                }
+                let log = handle_log(opcode, context.address, memory, stack, gas)?;
+                self.state.add_log(log);
+            }
+
+            // System Operations
+            opcodes::CALL | opcodes::CALLCODE | opcodes::DELEGATECALL | opcodes::STATICCALL => {
+                let kind = match opcode {
+                    opcodes::CALL => CallKind::Call,
+                    opcodes::CALLCODE => CallKind::CallCode,
+                    opcodes::DELEGATECALL => CallKind::DelegateCall,
+                    _ => CallKind::StaticCall,
+                };
+                let inputs = handle_call(
+                    self.spec,
+                    kind,
+                    context,
+                    &mut self.state,
+                    memory,
+                    stack,
+                    gas,
+                    *is_static,
+                )?;
+                frame.pending_call = Some(inputs);
+            }
+            opcodes::CREATE | opcodes::CREATE2 => {
+                let inputs =
+                    handle_create(self.spec, opcode, context, memory, stack, gas, *is_static)?;
+                frame.pending_create = Some(inputs);
+            }
+            opcodes::RETURN => {
+                frame.output = handle_return(memory, stack, gas)?;
+                frame.running = false;
+            }
+            opcodes::REVERT => {
+                frame.output = handle_return(memory, stack, gas)?;
+                frame.reverted = true;
+                frame.running = false;
+            }
+            opcodes::SELFDESTRUCT => {
+                if *is_static {
+                    return Err(EvmError::StateChangeInStaticContext);
+                }
+                handle_selfdestruct(self.spec, &mut self.state, context.address, stack, gas)?;
+                frame.running = false;
             }
 
             // Stack Manipulation
             opcodes::POP => {
-                self.stack.pop()?;
+                stack.pop()?;
             }
             _ if is_push(opcode) => {
-                handle_push(opcode, &mut self.stack, bytecode, &mut self.pc)?;
+                handle_push(opcode, stack, code, pc)?;
             }
             _ if is_dup(opcode) => {
-                handle_dup(opcode, &mut self.stack)?;
+                handle_dup(opcode, stack)?;
             }
             _ if is_swap(opcode) => {
-                handle_swap(opcode, &mut self.stack)?;
+                handle_swap(opcode, stack)?;
             }
 
             _ => return Err(EvmError::InvalidOpcode(opcode)),
@@ -106,32 +729,89 @@ fn execute_opcode(&mut self, opcode: u8, bytecode: &[u8]) -> Result<()> {
     }
 
     pub fn stack_top(&self) -> Result<U256> {
-        self.stack.top()
+        self.frame.stack.top()
     }
 
     pub fn stack(&self) -> &Stack {
-        &self.stack
+        &self.frame.stack
+    }
+
+    pub fn state(&self) -> &WorldState {
+        &self.state
     }
 
-    pub fn storage(&self) -> &Storage {
-        &self.storage
+    pub fn state_mut(&mut self) -> &mut WorldState {
+        &mut self.state
     }
 
     pub fn memory(&self) -> &Memory {
-        &self.memory
+        &self.frame.memory
+    }
+
+    pub fn block_env(&self) -> &BlockEnv {
+        &self.block
+    }
+
+    pub fn set_block_env(&mut self, block: BlockEnv) {
+        self.block = block;
+    }
+
+    pub fn spec(&self) -> SpecId {
+        self.spec
+    }
+
+    /// Selects the fork whose opcodes, gas schedule and precompiles apply
+    /// from the next execution on. Replaces any custom precompiles.
+    pub fn set_spec(&mut self, spec: SpecId) {
+        self.spec = spec;
+        self.precompiles = Precompiles::new(spec);
+    }
+
+    pub fn precompiles(&self) -> &Precompiles {
+        &self.precompiles
+    }
+
+    /// Allows installing custom precompiles or removing standard ones.
+    pub fn precompiles_mut(&mut self) -> &mut Precompiles {
+        &mut self.precompiles
+    }
+
+    pub fn balance(&self, address: Address) -> U256 {
+        self.state.balance(&address)
+    }
+
+    pub fn set_balance(&mut self, address: Address, balance: U256) {
+        self.state.set_balance(address, balance);
+    }
+
+    pub fn context(&self) -> &CallContext {
+        &self.frame.context
+    }
+
+    /// Events emitted by the last successful execution.
+    pub fn logs(&self) -> &[Log] {
+        self.state.logs()
+    }
+
+    pub fn gas(&self) -> &Gas {
+        &self.frame.gas
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::hex_to_bytes;
+    use crate::context::AccessListItem;
+    use crate::precompiles::{self, PrecompileOutput};
+    use crate::state::Account;
+    use crate::utils::{hex_to_bytes, keccak256};
+    use primitive_types::H256;
 
     #[test]
     fn test_push1_stop() {
         let mut evm = Evm::new();
         let bytecode = hex_to_bytes("0x6042").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(0x42));
     }
 
@@ -140,7 +820,7 @@ fn test_add() {
         let mut evm = Evm::new();
         // PUSH1 0x01, PUSH1 0x02, ADD
         let bytecode = hex_to_bytes("0x6001600201").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(3));
     }
 
@@ -149,7 +829,7 @@ fn test_mul() {
         let mut evm = Evm::new();
         // PUSH1 0x03, PUSH1 0x04, MUL
         let bytecode = hex_to_bytes("0x6003600402").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(12));
     }
 
@@ -158,7 +838,7 @@ fn test_sub() {
         let mut evm = Evm::new();
         // PUSH1 0x03, PUSH1 0x05, SUB
         let bytecode = hex_to_bytes("0x6003600503").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(2));
     }
 
@@ -167,7 +847,7 @@ fn test_div() {
         let mut evm = Evm::new();
         // PUSH1 0x02, PUSH1 0x06, DIV
         let bytecode = hex_to_bytes("0x6002600604").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(3));
     }
 
@@ -176,7 +856,7 @@ fn test_div_by_zero() {
         let mut evm = Evm::new();
         // PUSH1 0x00, PUSH1 0x06, DIV
         let bytecode = hex_to_bytes("0x6000600604").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::zero());
     }
 
@@ -185,7 +865,7 @@ fn test_mod() {
         let mut evm = Evm::new();
         // PUSH1 0x03, PUSH1 0x0a, MOD
         let bytecode = hex_to_bytes("0x6003600a06").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(1));
     }
 
@@ -194,8 +874,8 @@ fn test_dup1() {
         let mut evm = Evm::new();
         // PUSH1 0x42, DUP1
         let bytecode = hex_to_bytes("0x604280").unwrap();
-        evm.execute(&bytecode).unwrap();
-        assert_eq!(evm.stack.len(), 2);
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack().len(), 2);
         assert_eq!(evm.stack_top().unwrap(), U256::from(0x42));
     }
 
@@ -204,7 +884,7 @@ fn test_swap1() {
         let mut evm = Evm::new();
         // PUSH1 0x01, PUSH1 0x02, SWAP1
         let bytecode = hex_to_bytes("0x6001600290").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(1));
     }
 
@@ -213,8 +893,8 @@ fn test_pop() {
         let mut evm = Evm::new();
         // PUSH1 0x01, PUSH1 0x02, POP
         let bytecode = hex_to_bytes("0x6001600250").unwrap();
-        evm.execute(&bytecode).unwrap();
-        assert_eq!(evm.stack.len(), 1);
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack().len(), 1);
         assert_eq!(evm.stack_top().unwrap(), U256::from(1));
     }
 
@@ -223,7 +903,7 @@ fn test_sstore_sload() {
         let mut evm = Evm::new();
         // PUSH1 0x64 (value=100), PUSH1 0x01 (key=1), SSTORE, PUSH1 0x01, SLOAD
         let bytecode = hex_to_bytes("0x6064600155600154").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(100));
     }
 
@@ -232,7 +912,7 @@ fn test_iszero() {
         let mut evm = Evm::new();
         // PUSH1 0x00, ISZERO
         let bytecode = hex_to_bytes("0x600015").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::one());
     }
 
@@ -241,7 +921,7 @@ fn test_lt() {
         let mut evm = Evm::new();
         // PUSH1 0x02, PUSH1 0x01, LT (1 < 2 = true)
         let bytecode = hex_to_bytes("0x6002600110").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::one());
     }
 
@@ -250,7 +930,7 @@ fn test_gt() {
         let mut evm = Evm::new();
         // PUSH1 0x01, PUSH1 0x02, GT (2 > 1 = true)
         let bytecode = hex_to_bytes("0x6001600211").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::one());
     }
 
@@ -259,7 +939,7 @@ fn test_eq() {
         let mut evm = Evm::new();
         // PUSH1 0x05, PUSH1 0x05, EQ
         let bytecode = hex_to_bytes("0x6005600514").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::one());
     }
 
@@ -268,7 +948,7 @@ fn test_and() {
         let mut evm = Evm::new();
         // PUSH1 0x0f, PUSH1 0xff, AND
         let bytecode = hex_to_bytes("0x600f60ff16").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(0x0f));
     }
 
@@ -277,7 +957,7 @@ fn test_or() {
         let mut evm = Evm::new();
         // PUSH1 0x0f, PUSH1 0xf0, OR
         let bytecode = hex_to_bytes("0x600f60f017").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(0xff));
     }
 
@@ -286,7 +966,1180 @@ fn test_xor() {
         let mut evm = Evm::new();
         // PUSH1 0xff, PUSH1 0xf0, XOR
         let bytecode = hex_to_bytes("0x60ff60f018").unwrap();
-        evm.execute(&bytecode).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
         assert_eq!(evm.stack_top().unwrap(), U256::from(0x0f));
     }
+
+    #[test]
+    fn test_jump() {
+        let mut evm = Evm::new();
+        // PUSH1 0x05, JUMP, PUSH1 0xff, JUMPDEST, PUSH1 0x01
+        let bytecode = hex_to_bytes("0x60055660ff5b6001").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack().len(), 1);
+        assert_eq!(evm.stack_top().unwrap(), U256::one());
+    }
+
+    #[test]
+    fn test_jumpi_loop() {
+        let mut evm = Evm::new();
+        // PUSH1 0x03, JUMPDEST, PUSH1 0x01, SWAP1, SUB, DUP1, PUSH1 0x02, JUMPI
+        // (counts down from 3 to 0)
+        let bytecode = hex_to_bytes("0x60035b600190038060025700").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack().len(), 1);
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn test_jump_into_push_data() {
+        let mut evm = Evm::new();
+        // PUSH1 0x04, JUMP, PUSH1 0x5b
+        let bytecode = hex_to_bytes("0x600456605b").unwrap();
+        assert_eq!(
+            evm.execute(&bytecode),
+            ExecutionResult::Halt {
+                reason: EvmError::InvalidJump(U256::from(4))
+            }
+        );
+    }
+
+    #[test]
+    fn test_pc() {
+        let mut evm = Evm::new();
+        // PUSH1 0x00, POP, PC
+        let bytecode = hex_to_bytes("0x60005058").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(3));
+    }
+
+    #[test]
+    fn test_mstore_mload() {
+        let mut evm = Evm::new();
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x00, MLOAD
+        let bytecode = hex_to_bytes("0x602a60005260005100").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(0x2a));
+    }
+
+    #[test]
+    fn test_mstore8_msize() {
+        let mut evm = Evm::new();
+        // PUSH1 0xff, PUSH1 0x20, MSTORE8, MSIZE
+        let bytecode = hex_to_bytes("0x60ff60205359").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(64));
+        assert_eq!(evm.frame.memory.read(U256::from(32)).unwrap(), 0xff);
+    }
+
+    #[test]
+    fn test_mcopy() {
+        let mut evm = Evm::new();
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, PUSH1 0x20, MCOPY,
+        // PUSH1 0x20, MLOAD
+        let bytecode = hex_to_bytes("0x602a6000526020600060205e602051").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(0x2a));
+    }
+
+    #[test]
+    fn test_gas_used() {
+        let mut evm = Evm::new();
+        // PUSH1 0x01, PUSH1 0x02, ADD
+        let bytecode = hex_to_bytes("0x6001600201").unwrap();
+        assert!(evm.execute_with_gas(&bytecode, 100).is_success());
+        assert_eq!(evm.gas().used(), 9);
+        assert_eq!(evm.gas().remaining(), 91);
+    }
+
+    #[test]
+    fn test_exp_dynamic_gas() {
+        let mut evm = Evm::new();
+        // PUSH2 0x0100, PUSH1 0x02, EXP (exponent is two bytes long)
+        let bytecode = hex_to_bytes("0x61010060020a").unwrap();
+        assert!(evm.execute_with_gas(&bytecode, 1000).is_success());
+        assert_eq!(evm.gas().used(), 3 + 3 + 10 + 2 * 50);
+    }
+
+    #[test]
+    fn test_out_of_gas() {
+        let mut evm = Evm::new();
+        // PUSH1 0x01, PUSH1 0x02, ADD
+        let bytecode = hex_to_bytes("0x6001600201").unwrap();
+        assert_eq!(
+            evm.execute_with_gas(&bytecode, 8),
+            ExecutionResult::Halt {
+                reason: EvmError::OutOfGas
+            }
+        );
+        assert_eq!(evm.gas().remaining(), 0);
+    }
+
+    #[test]
+    fn test_infinite_loop_runs_out_of_gas() {
+        let mut evm = Evm::new();
+        // JUMPDEST, PUSH1 0x00, JUMP
+        let bytecode = hex_to_bytes("0x5b600056").unwrap();
+        assert_eq!(
+            evm.execute_with_gas(&bytecode, 10_000),
+            ExecutionResult::Halt {
+                reason: EvmError::OutOfGas
+            }
+        );
+        assert_eq!(evm.gas().used(), 10_000);
+    }
+
+    #[test]
+    fn test_memory_expansion_gas() {
+        let mut evm = Evm::new();
+        // PUSH1 0x01, PUSH1 0x00, MSTORE
+        let bytecode = hex_to_bytes("0x6001600052").unwrap();
+        assert!(evm.execute_with_gas(&bytecode, 100).is_success());
+        assert_eq!(evm.gas().used(), 3 + 3 + 3 + 3);
+    }
+
+    #[test]
+    fn test_mload_huge_offset_does_not_panic() {
+        let mut evm = Evm::new();
+        // PUSH32 0xff..ff, MLOAD
+        let bytecode = hex_to_bytes(&format!("0x7f{}51", "ff".repeat(32))).unwrap();
+        assert_eq!(
+            evm.execute_with_gas(&bytecode, 1_000_000),
+            ExecutionResult::Halt {
+                reason: EvmError::MemoryOutOfBounds
+            }
+        );
+        assert_eq!(evm.gas().remaining(), 0);
+    }
+
+    #[test]
+    fn test_execute_is_bounded_by_block_gas_limit() {
+        let mut evm = Evm::new();
+        // PUSH4 0xffffff00, MLOAD: 4 GiB of memory, far beyond one block.
+        let bytecode = hex_to_bytes("0x63ffffff0051").unwrap();
+        assert_eq!(
+            evm.execute(&bytecode),
+            ExecutionResult::Halt {
+                reason: EvmError::OutOfGas
+            }
+        );
+        assert_eq!(evm.gas().used(), BlockEnv::default().gas_limit);
+    }
+
+    #[test]
+    fn test_keccak256() {
+        let mut evm = Evm::new();
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, KECCAK256
+        let bytecode = hex_to_bytes("0x602a6000526020600020").unwrap();
+        assert!(evm.execute_with_gas(&bytecode, 1000).is_success());
+
+        let expected = keccak256(&U256::from(0x2a).to_big_endian());
+        assert_eq!(evm.stack_top().unwrap(), U256::from_big_endian(&expected));
+        // 4 * PUSH1 + MSTORE + one word of memory + KECCAK256 + one word hashed
+        assert_eq!(evm.gas().used(), 4 * 3 + 3 + 3 + 30 + 6);
+    }
+
+    #[test]
+    fn test_return() {
+        let mut evm = Evm::new();
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, RETURN
+        let bytecode = hex_to_bytes("0x602a60005260206000f3").unwrap();
+        let result = evm.execute_with_gas(&bytecode, 1000);
+        assert_eq!(
+            result,
+            ExecutionResult::Success {
+                output: U256::from(0x2a).to_big_endian().to_vec(),
+                gas_used: 4 * 3 + 3 + 3,
+                gas_refunded: 0,
+                logs: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_revert_keeps_unused_gas() {
+        let mut evm = Evm::new();
+        // PUSH1 0x01, PUSH1 0x1f, MSTORE8, PUSH1 0x01, PUSH1 0x1f, REVERT, PUSH1 0x00
+        let bytecode = hex_to_bytes("0x6001601f536001601ffd6000").unwrap();
+        let result = evm.execute_with_gas(&bytecode, 1000);
+        assert_eq!(
+            result,
+            ExecutionResult::Revert {
+                output: vec![0x01],
+                gas_used: 4 * 3 + 3 + 3,
+            }
+        );
+        assert_eq!(evm.gas().remaining(), 1000 - 18);
+    }
+
+    #[test]
+    fn test_halt_on_stack_underflow() {
+        let mut evm = Evm::new();
+        // ADD
+        let bytecode = hex_to_bytes("0x01").unwrap();
+        assert_eq!(
+            evm.execute_with_gas(&bytecode, 1000),
+            ExecutionResult::Halt {
+                reason: EvmError::StackUnderflow
+            }
+        );
+        assert_eq!(evm.gas().used(), 1000);
+    }
+
+    #[test]
+    fn test_call_context() {
+        let mut evm = Evm::new();
+        let context = CallContext {
+            caller: Address::repeat_byte(0x01),
+            origin: Address::repeat_byte(0x02),
+            address: Address::repeat_byte(0x03),
+            gas_price: U256::from(7),
+            value: U256::from(1000),
+            data: vec![0xa9, 0x05, 0x9c, 0xbb],
+            gas_limit: 10_000,
+            access_list: Vec::new(),
+        };
+        // CALLER, ORIGIN, ADDRESS, CALLVALUE, CALLDATASIZE
+        let bytecode = hex_to_bytes("0x3332303436").unwrap();
+        assert!(evm.execute_with_context(&bytecode, context).is_success());
+
+        let stack = evm.stack();
+        assert_eq!(stack.peek(0).unwrap(), U256::from(4));
+        assert_eq!(stack.peek(1).unwrap(), U256::from(1000));
+        assert_eq!(stack.peek(2).unwrap(), U256::from_big_endian(&[0x03; 20]));
+        assert_eq!(stack.peek(3).unwrap(), U256::from_big_endian(&[0x02; 20]));
+        assert_eq!(stack.peek(4).unwrap(), U256::from_big_endian(&[0x01; 20]));
+    }
+
+    #[test]
+    fn test_function_selector_dispatch() {
+        let mut evm = Evm::new();
+        let context = CallContext {
+            data: hex_to_bytes("0xa9059cbb").unwrap(),
+            ..Default::default()
+        };
+        // PUSH1 0x00, CALLDATALOAD, PUSH1 0xe0, SHR (the 4-byte selector)
+        let bytecode = hex_to_bytes("0x60003560e01c").unwrap();
+        assert!(evm.execute_with_context(&bytecode, context).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(0xa9059cbbu32));
+    }
+
+    #[test]
+    fn test_codecopy_returns_own_code() {
+        let mut evm = Evm::new();
+        // CODESIZE, PUSH1 0x00, PUSH1 0x00, CODECOPY, CODESIZE, PUSH1 0x00, RETURN
+        let bytecode = hex_to_bytes("0x386000600039386000f3").unwrap();
+        let result = evm.execute(&bytecode);
+        assert_eq!(result.output(), bytecode.as_slice());
+    }
+
+    #[test]
+    fn test_block_env() {
+        let mut evm = Evm::new();
+        evm.set_block_env(BlockEnv {
+            number: U256::from(100),
+            timestamp: U256::from(1_700_000_000),
+            basefee: U256::from(10),
+            chain_id: 10,
+            ..Default::default()
+        });
+        // NUMBER, TIMESTAMP, BASEFEE, CHAINID, GASLIMIT
+        let bytecode = hex_to_bytes("0x4342484645").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+
+        let stack = evm.stack();
+        assert_eq!(stack.peek(0).unwrap(), U256::from(30_000_000));
+        assert_eq!(stack.peek(1).unwrap(), U256::from(10));
+        assert_eq!(stack.peek(2).unwrap(), U256::from(10));
+        assert_eq!(stack.peek(3).unwrap(), U256::from(1_700_000_000));
+        assert_eq!(stack.peek(4).unwrap(), U256::from(100));
+    }
+
+    #[test]
+    fn test_selfbalance_and_gasprice() {
+        let mut evm = Evm::new();
+        let address = Address::repeat_byte(0x42);
+        evm.set_balance(address, U256::from(5000));
+        let context = CallContext {
+            address,
+            gas_price: U256::from(3),
+            ..Default::default()
+        };
+        // SELFBALANCE, GASPRICE
+        let bytecode = hex_to_bytes("0x473a").unwrap();
+        assert!(evm.execute_with_context(&bytecode, context).is_success());
+        assert_eq!(evm.stack().peek(0).unwrap(), U256::from(3));
+        assert_eq!(evm.stack().peek(1).unwrap(), U256::from(5000));
+    }
+
+    #[test]
+    fn test_log1() {
+        let mut evm = Evm::new();
+        let context = CallContext {
+            address: Address::repeat_byte(0xee),
+            ..Default::default()
+        };
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x07, PUSH1 0x20, PUSH1 0x00, LOG1
+        let bytecode = hex_to_bytes("0x602a600052600760206000a1").unwrap();
+        let result = evm.execute_with_context(&bytecode, context);
+
+        let expected = Log {
+            address: Address::repeat_byte(0xee),
+            topics: vec![H256::from_low_u64_be(7)],
+            data: U256::from(0x2a).to_big_endian().to_vec(),
+        };
+        assert_eq!(result.logs(), std::slice::from_ref(&expected));
+        assert_eq!(evm.logs(), std::slice::from_ref(&expected));
+        // 5 * PUSH1 + MSTORE + one word of memory + LOG1 + 32 bytes of data
+        assert_eq!(evm.gas().used(), 5 * 3 + 3 + 3 + 750 + 32 * 8);
+    }
+
+    #[test]
+    fn test_revert_discards_logs() {
+        let mut evm = Evm::new();
+        // PUSH1 0x00, PUSH1 0x00, LOG0, PUSH1 0x00, PUSH1 0x00, REVERT
+        let bytecode = hex_to_bytes("0x60006000a060006000fd").unwrap();
+        let result = evm.execute(&bytecode);
+        assert!(result.is_revert());
+        assert!(result.logs().is_empty());
+        assert!(evm.logs().is_empty());
+    }
+
+    #[test]
+    fn test_storage_scoped_to_executing_address() {
+        let mut evm = Evm::new();
+        let a = Address::repeat_byte(0x0a);
+        let b = Address::repeat_byte(0x0b);
+        // PUSH1 0x64, PUSH1 0x01, SSTORE
+        let bytecode = hex_to_bytes("0x6064600155").unwrap();
+        let context = CallContext {
+            address: a,
+            ..Default::default()
+        };
+        assert!(evm.execute_with_context(&bytecode, context).is_success());
+
+        assert_eq!(evm.state().sload(&a, &U256::one()), U256::from(100));
+        assert_eq!(evm.state().sload(&b, &U256::one()), U256::zero());
+    }
+
+    #[test]
+    fn test_balance_and_extcode() {
+        let mut evm = Evm::new();
+        let other = Address::repeat_byte(0x0b);
+        evm.state_mut().insert_account(
+            other,
+            Account {
+                balance: U256::from(77),
+                code: vec![0x60, 0x00],
+                ..Default::default()
+            },
+        );
+        // PUSH20 other, BALANCE, PUSH20 other, EXTCODESIZE, PUSH20 other, EXTCODEHASH
+        let push_other = format!("73{}", "0b".repeat(20));
+        let bytecode =
+            hex_to_bytes(&format!("0x{push_other}31{push_other}3b{push_other}3f")).unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+
+        let stack = evm.stack();
+        assert_eq!(
+            stack.peek(0).unwrap(),
+            U256::from_big_endian(&keccak256(&[0x60, 0x00]))
+        );
+        assert_eq!(stack.peek(1).unwrap(), U256::from(2));
+        assert_eq!(stack.peek(2).unwrap(), U256::from(77));
+        // Only the first access to `other` is cold.
+        assert_eq!(evm.gas().used(), 3 * 3 + 2600 + 2 * 100);
+    }
+
+    #[test]
+    fn test_cold_and_warm_sload() {
+        let mut evm = Evm::new();
+        // PUSH1 0x00, SLOAD, PUSH1 0x00, SLOAD
+        let bytecode = hex_to_bytes("0x600054600054").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.gas().used(), 3 + 2100 + 3 + 100);
+
+        // Accessed slots are forgotten between transactions.
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.gas().used(), 3 + 2100 + 3 + 100);
+    }
+
+    #[test]
+    fn test_opcodes_gated_by_spec() {
+        let mut evm = Evm::new();
+        evm.set_spec(SpecId::Merge);
+        // PUSH0
+        assert_eq!(
+            evm.execute(&hex_to_bytes("0x5f").unwrap()),
+            ExecutionResult::Halt {
+                reason: EvmError::InvalidOpcode(0x5f)
+            }
+        );
+
+        evm.set_spec(SpecId::Byzantium);
+        // PUSH1 0x01, PUSH1 0x01, SHL
+        assert_eq!(
+            evm.execute(&hex_to_bytes("0x600160011b").unwrap()),
+            ExecutionResult::Halt {
+                reason: EvmError::InvalidOpcode(0x1b)
+            }
+        );
+
+        evm.set_spec(SpecId::Constantinople);
+        assert!(
+            evm.execute(&hex_to_bytes("0x600160011b").unwrap())
+                .is_success()
+        );
+        assert_eq!(evm.stack_top().unwrap(), U256::from(2));
+    }
+
+    #[test]
+    fn test_every_named_opcode_is_executed() {
+        // The disassembler's table must not name an opcode the interpreter
+        // treats as unassigned, short of these. INVALID halts on purpose;
+        // the others are assigned but not implemented yet.
+        const UNEXECUTED: [u8; 3] = [opcodes::INVALID, opcodes::BLOCKHASH, opcodes::BLOBHASH];
+        for opcode in (0..=u8::MAX).filter(|&op| opcodes::info(op).is_some()) {
+            if UNEXECUTED.contains(&opcode) {
+                continue;
+            }
+            let mut evm = Evm::new();
+            // Enough zeros on the stack for any opcode, SWAP16 needing most.
+            let mut bytecode = vec![opcodes::PUSH0; 17];
+            bytecode.push(opcode);
+            assert_ne!(
+                evm.execute(&bytecode),
+                ExecutionResult::Halt {
+                    reason: EvmError::InvalidOpcode(opcode)
+                },
+                "{}",
+                opcodes::info(opcode).unwrap().mnemonic
+            );
+        }
+    }
+
+    #[test]
+    fn test_gas_schedule_by_spec() {
+        let mut evm = Evm::new();
+        // PUSH1 0x00, SLOAD, PUSH1 0x00, SLOAD
+        let bytecode = hex_to_bytes("0x600054600054").unwrap();
+
+        evm.set_spec(SpecId::Frontier);
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.gas().used(), 3 + 50 + 3 + 50);
+
+        evm.set_spec(SpecId::Tangerine);
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.gas().used(), 3 + 200 + 3 + 200);
+
+        evm.set_spec(SpecId::Istanbul);
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.gas().used(), 3 + 800 + 3 + 800);
+    }
+
+    #[test]
+    fn test_sstore_net_metering() {
+        // (code, original value, gas spent, refund) from the EIP-3529 test
+        // cases, with the slot pre-warmed by an access list.
+        let cases = [
+            ("0x60006000556000600055", 0, 212, 0),
+            ("0x60006000556001600055", 0, 20112, 0),
+            ("0x60016000556000600055", 0, 20112, 19900),
+            ("0x60016000556002600055", 0, 20112, 0),
+            ("0x60016000556001600055", 0, 20112, 0),
+            ("0x60006000556000600055", 1, 3012, 4800),
+            ("0x60006000556001600055", 1, 3012, 2800),
+            ("0x60006000556002600055", 1, 3012, 0),
+            ("0x60026000556000600055", 1, 3012, 4800),
+            ("0x60026000556003600055", 1, 3012, 0),
+            ("0x60026000556001600055", 1, 3012, 2800),
+            ("0x60026000556002600055", 1, 3012, 0),
+            ("0x60016000556000600055", 1, 3012, 4800),
+            ("0x60016000556002600055", 1, 3012, 0),
+            ("0x60016000556001600055", 1, 212, 0),
+            ("0x600160005560006000556001600055", 0, 40118, 19900),
+            ("0x600060005560016000556000600055", 1, 5918, 7600),
+        ];
+
+        for (code, original, spent, refund) in cases {
+            let mut evm = Evm::new();
+            evm.state_mut()
+                .sstore(Address::zero(), U256::zero(), U256::from(original));
+            let context = CallContext {
+                access_list: vec![AccessListItem {
+                    address: Address::zero(),
+                    storage_keys: vec![U256::zero()],
+                }],
+                ..Default::default()
+            };
+
+            let result = evm.execute_with_context(&hex_to_bytes(code).unwrap(), context);
+            let refunded = refund.min(spent / 5);
+            assert_eq!(evm.gas().used(), spent, "{code}");
+            assert_eq!(result.gas_refunded(), refunded, "{code}");
+            assert!(matches!(
+                result,
+                ExecutionResult::Success { gas_used, .. } if gas_used == spent - refunded
+            ));
+        }
+    }
+
+    #[test]
+    fn test_reverted_call_discards_refund() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xcc);
+        evm.state_mut().sstore(callee, U256::zero(), U256::one());
+        // SSTORE(0, 0), REVERT(0, 0)
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x600060005560006000fd").unwrap());
+        // CALL(GAS, callee, 0, 0, 0, 0, 0)
+        let bytecode =
+            hex_to_bytes(&format!("0x60006000600060006000{}5af1", push20(callee))).unwrap();
+
+        let result = evm.execute(&bytecode);
+        assert!(result.is_success());
+        assert_eq!(result.gas_refunded(), 0);
+        assert_eq!(evm.state().sload(&callee, &U256::zero()), U256::one());
+    }
+
+    #[test]
+    fn test_access_list_prewarms() {
+        let mut evm = Evm::new();
+        let other = Address::repeat_byte(0x0b);
+        let context = CallContext {
+            access_list: vec![
+                AccessListItem {
+                    address: Address::zero(),
+                    storage_keys: vec![U256::zero()],
+                },
+                AccessListItem {
+                    address: other,
+                    storage_keys: Vec::new(),
+                },
+            ],
+            ..Default::default()
+        };
+        // PUSH1 0x00, SLOAD, PUSH20 other, BALANCE
+        let bytecode = hex_to_bytes(&format!("0x60005473{}31", "0b".repeat(20))).unwrap();
+        assert!(evm.execute_with_context(&bytecode, context).is_success());
+        assert_eq!(evm.gas().used(), 3 + 100 + 3 + 100);
+    }
+
+    fn push20(address: Address) -> String {
+        format!("73{}", hex::encode(address.as_bytes()))
+    }
+
+    #[test]
+    fn test_call_returns_data() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xbb);
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, RETURN
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x602a60005260206000f3").unwrap());
+
+        // CALL(GAS, callee, 0, 0, 0, 0, 32), PUSH1 0x00, MLOAD
+        let bytecode = hex_to_bytes(&format!(
+            "0x60206000600060006000{}5af1600051",
+            push20(callee)
+        ))
+        .unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+
+        assert_eq!(evm.stack().peek(0).unwrap(), U256::from(0x2a));
+        assert_eq!(evm.stack().peek(1).unwrap(), U256::one());
+    }
+
+    #[test]
+    fn test_call_transfers_value() {
+        let mut evm = Evm::new();
+        let sender = Address::repeat_byte(0x01);
+        let wallet = Address::repeat_byte(0x02);
+        let recipient = Address::repeat_byte(0x03);
+        evm.set_balance(sender, U256::from(100));
+        // CALL(0, recipient, 10, 0, 0, 0, 0)
+        let code = format!("0x600060006000600060{:02x}{}6000f1", 10, push20(recipient));
+        evm.state_mut()
+            .set_code(wallet, hex_to_bytes(&code).unwrap());
+
+        let result = evm.call(CallContext {
+            caller: sender,
+            address: wallet,
+            value: U256::from(40),
+            ..Default::default()
+        });
+
+        assert!(result.is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::one());
+        assert_eq!(evm.balance(sender), U256::from(60));
+        assert_eq!(evm.balance(wallet), U256::from(30));
+        assert_eq!(evm.balance(recipient), U256::from(10));
+    }
+
+    #[test]
+    fn test_call_insufficient_balance_fails() {
+        let mut evm = Evm::new();
+        // CALL(GAS, 0xbb.., 1, 0, 0, 0, 0)
+        let bytecode = hex_to_bytes(&format!(
+            "0x60006000600060006001{}5af1",
+            push20(Address::repeat_byte(0xbb))
+        ))
+        .unwrap();
+        assert!(evm.execute_with_gas(&bytecode, 100_000).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+        // The call was refused, so the forwarded gas came back along with
+        // the stipend, just as if the callee had spent nothing.
+        let value_and_new_account = gas::CALL_VALUE + gas::NEW_ACCOUNT;
+        assert_eq!(
+            evm.gas().used(),
+            6 * 3 + 2 + gas::COLD_ACCOUNT_ACCESS + value_and_new_account - gas::CALL_STIPEND
+        );
+    }
+
+    #[test]
+    fn test_delegatecall_uses_caller_storage() {
+        let mut evm = Evm::new();
+        let proxy = Address::repeat_byte(0xaa);
+        let logic = Address::repeat_byte(0xbb);
+        // PUSH1 0x2a, PUSH1 0x00, SSTORE
+        evm.state_mut()
+            .set_code(logic, hex_to_bytes("0x602a600055").unwrap());
+        // DELEGATECALL(GAS, logic, 0, 0, 0, 0)
+        let code = format!("0x6000600060006000{}5af4", push20(logic));
+        evm.state_mut()
+            .set_code(proxy, hex_to_bytes(&code).unwrap());
+
+        let result = evm.call(CallContext {
+            address: proxy,
+            ..Default::default()
+        });
+
+        assert!(result.is_success());
+        assert_eq!(evm.state().sload(&proxy, &U256::zero()), U256::from(0x2a));
+        assert_eq!(evm.state().sload(&logic, &U256::zero()), U256::zero());
+    }
+
+    #[test]
+    fn test_staticcall_forbids_sstore() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xcc);
+        // PUSH1 0x01, PUSH1 0x00, SSTORE
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x6001600055").unwrap());
+        // STATICCALL(GAS, callee, 0, 0, 0, 0)
+        let bytecode = hex_to_bytes(&format!("0x6000600060006000{}5afa", push20(callee))).unwrap();
+
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+        assert_eq!(evm.state().sload(&callee, &U256::zero()), U256::zero());
+    }
+
+    #[test]
+    fn test_reverted_call_discards_logs() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xdd);
+        // PUSH1 0x00, PUSH1 0x00, LOG0, PUSH1 0x00, PUSH1 0x00, REVERT
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x60006000a060006000fd").unwrap());
+        // CALL(GAS, callee, 0, 0, 0, 0, 0), PUSH1 0x00, PUSH1 0x00, LOG0
+        let bytecode = hex_to_bytes(&format!(
+            "0x60006000600060006000{}5af160006000a0",
+            push20(callee)
+        ))
+        .unwrap();
+
+        let result = evm.execute(&bytecode);
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+        assert_eq!(result.logs().len(), 1);
+        assert_eq!(result.logs()[0].address, Address::zero());
+    }
+
+    #[test]
+    fn test_reverted_call_undoes_state() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xdd);
+        evm.set_balance(Address::zero(), U256::from(10));
+        // PUSH1 0x01, PUSH1 0x00, SSTORE, PUSH1 0x00, PUSH1 0x00, REVERT
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x600160005560006000fd").unwrap());
+        // CALL(GAS, callee, 5, 0, 0, 0, 0), PUSH1 0x02, PUSH1 0x00, SSTORE
+        let bytecode = hex_to_bytes(&format!(
+            "0x60006000600060006005{}5af1600260005500",
+            push20(callee)
+        ))
+        .unwrap();
+
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+        assert_eq!(evm.state().sload(&callee, &U256::zero()), U256::zero());
+        assert_eq!(evm.balance(callee), U256::zero());
+        assert_eq!(evm.balance(Address::zero()), U256::from(10));
+        assert_eq!(
+            evm.state().sload(&Address::zero(), &U256::zero()),
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn test_reverted_transaction_refunds_value() {
+        let mut evm = Evm::new();
+        let sender = Address::repeat_byte(0x01);
+        let contract = Address::repeat_byte(0x02);
+        evm.set_balance(sender, U256::from(100));
+        let checkpoint = evm.state().checkpoint();
+        // PUSH1 0x01, PUSH1 0x00, SSTORE, PUSH1 0x00, PUSH1 0x00, REVERT
+        evm.state_mut()
+            .set_code(contract, hex_to_bytes("0x600160005560006000fd").unwrap());
+
+        let result = evm.call(CallContext {
+            caller: sender,
+            address: contract,
+            value: U256::from(40),
+            ..Default::default()
+        });
+
+        assert!(result.is_revert());
+        assert_eq!(evm.balance(sender), U256::from(100));
+        assert_eq!(evm.state().sload(&contract, &U256::zero()), U256::zero());
+
+        // Earlier changes can still be rolled back from the outside.
+        assert!(evm.state_mut().revert_to(checkpoint));
+        assert!(!evm.state().exists(&contract));
+        assert_eq!(evm.balance(sender), U256::from(100));
+    }
+
+    #[test]
+    fn test_transient_storage() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xcc);
+        // TSTORE(0, 1), REVERT(0, 0)
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x600160005d60006000fd").unwrap());
+        // TSTORE(0, 7), CALL(GAS, callee, 0, 0, 0, 0, 0), TLOAD(0)
+        let bytecode = hex_to_bytes(&format!(
+            "0x600760005d60006000600060006000{}5af160005c",
+            push20(callee)
+        ))
+        .unwrap();
+
+        let result = evm.execute(&bytecode);
+        assert!(result.is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(7));
+        assert_eq!(evm.stack().peek(1).unwrap(), U256::zero());
+        // Gone once the transaction is over.
+        assert_eq!(evm.state().tload(&callee, &U256::zero()), U256::zero());
+        assert_eq!(
+            evm.state().tload(&Address::zero(), &U256::zero()),
+            U256::zero()
+        );
+
+        // TSTORE(0, 7), TLOAD(0)
+        let bytecode = hex_to_bytes("0x600760005d60005c").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.gas().used(), 3 * 3 + 2 * gas::WARM_STORAGE_READ);
+    }
+
+    #[test]
+    fn test_tstore_in_static_context() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xcc);
+        // TSTORE(0, 1)
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x600160005d").unwrap());
+        // STATICCALL(GAS, callee, 0, 0, 0, 0)
+        let bytecode = hex_to_bytes(&format!("0x6000600060006000{}5afa", push20(callee))).unwrap();
+
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn test_revert_reason_bubbles_up() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xdd);
+        // PUSH1 0x2a, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, REVERT
+        evm.state_mut()
+            .set_code(callee, hex_to_bytes("0x602a60005260206000fd").unwrap());
+        // CALL(GAS, callee, 0, 0, 0, 0, 0),
+        // RETURNDATACOPY(0, 0, RETURNDATASIZE), REVERT(0, RETURNDATASIZE)
+        let bytecode = hex_to_bytes(&format!(
+            "0x60006000600060006000{}5af13d600060003e3d6000fd",
+            push20(callee)
+        ))
+        .unwrap();
+
+        let result = evm.execute(&bytecode);
+        assert!(result.is_revert());
+        assert_eq!(result.output(), U256::from(0x2a).to_big_endian().as_slice());
+    }
+
+    #[test]
+    fn test_returndatacopy_out_of_bounds() {
+        let mut evm = Evm::new();
+        // RETURNDATACOPY(0, 0, 1) with no previous call
+        let bytecode = hex_to_bytes("0x6001600060003e").unwrap();
+        assert_eq!(
+            evm.execute(&bytecode),
+            ExecutionResult::Halt {
+                reason: EvmError::ReturnDataOutOfBounds
+            }
+        );
+    }
+
+    #[test]
+    fn test_staticcall_sha256_precompile() {
+        let mut evm = Evm::new();
+        // MSTORE(0, "abc"), STATICCALL(GAS, 0x02, 29, 3, 0, 32), PUSH1 0x00, MLOAD
+        let bytecode = hex_to_bytes("0x62616263600052602060006003601d60025afa600051").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+
+        let expected = crate::utils::sha256(b"abc");
+        assert_eq!(
+            evm.stack().peek(0).unwrap(),
+            U256::from_big_endian(&expected)
+        );
+        assert_eq!(evm.stack().peek(1).unwrap(), U256::one());
+    }
+
+    #[test]
+    fn test_staticcall_bn254_add_precompile() {
+        let mut evm = Evm::new();
+        // MSTORE the generator (1, 2) twice, STATICCALL(GAS, 0x06, 0, 128, 0, 64),
+        // PUSH1 0x00, MLOAD
+        let bytecode = hex_to_bytes(
+            "0x6001600052600260205260016040526002606052\
+             60406000608060006006\
+             5afa600051",
+        )
+        .unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+
+        let doubled_x = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3";
+        assert_eq!(
+            evm.stack().peek(0).unwrap(),
+            U256::from_big_endian(&hex_to_bytes(doubled_x).unwrap())
+        );
+        assert_eq!(evm.stack().peek(1).unwrap(), U256::one());
+    }
+
+    #[test]
+    fn test_precompile_out_of_gas_consumes_forwarded_gas() {
+        let mut evm = Evm::new();
+        // STATICCALL(59, 0x02, 0, 0, 0, 0), RETURNDATASIZE
+        let bytecode = hex_to_bytes("0x60006000600060006002603bfa3d").unwrap();
+        assert!(evm.execute(&bytecode).is_success());
+
+        assert_eq!(evm.stack().peek(0).unwrap(), U256::zero());
+        assert_eq!(evm.stack().peek(1).unwrap(), U256::zero());
+        // Precompiles are warm from the start (EIP-2929).
+        assert_eq!(evm.gas().used(), 6 * 3 + 100 + 59 + 2);
+    }
+
+    #[test]
+    fn test_call_precompile_directly() {
+        let mut evm = Evm::new();
+        let result = evm.call(CallContext {
+            address: precompiles::IDENTITY,
+            data: vec![1, 2, 3],
+            gas_limit: 100_000,
+            ..Default::default()
+        });
+        assert_eq!(result.output(), &[1, 2, 3]);
+        assert_eq!(evm.gas().used(), 15 + 3);
+    }
+
+    #[test]
+    fn test_custom_precompile() {
+        let mut evm = Evm::new();
+        let address = precompiles::precompile_address(0x42);
+        evm.precompiles_mut()
+            .insert(address, |input: &[u8], _gas_limit| {
+                Ok(PrecompileOutput::new(
+                    7,
+                    input.iter().rev().copied().collect(),
+                ))
+            });
+        let result = evm.call(CallContext {
+            address,
+            data: vec![1, 2, 3],
+            gas_limit: 100_000,
+            ..Default::default()
+        });
+        assert_eq!(result.output(), &[3, 2, 1]);
+        assert_eq!(evm.gas().used(), 7);
+    }
+
+    // PUSH10 <runtime: RETURN(42)>, PUSH1 0x00, MSTORE, PUSH1 0x0a, PUSH1 0x16, RETURN
+    const INIT_CODE: &str = "69602a60005260206000f3600052600a6016f3";
+    const RUNTIME_CODE: &str = "602a60005260206000f3";
+
+    #[test]
+    fn test_deploy() {
+        let mut evm = Evm::new();
+        let (address, result) = evm.deploy(&hex_to_bytes(INIT_CODE).unwrap(), U256::zero());
+
+        assert!(result.is_success());
+        assert_eq!(address, create_address(Address::zero(), 0));
+        assert_eq!(
+            evm.state().code(&address),
+            hex_to_bytes(RUNTIME_CODE).unwrap()
+        );
+        assert_eq!(evm.state().nonce(&address), 1);
+        assert_eq!(evm.state().nonce(&Address::zero()), 1);
+
+        let result = evm.call(CallContext {
+            address,
+            ..Default::default()
+        });
+        assert_eq!(result.output(), U256::from(42).to_big_endian().as_slice());
+    }
+
+    #[test]
+    fn test_create_from_contract() {
+        let mut evm = Evm::new();
+        let factory = Address::repeat_byte(0xfa);
+        evm.state_mut().set_nonce(factory, 5);
+        evm.set_balance(factory, U256::from(10));
+        // MSTORE(0, PUSH19 <initcode>), CREATE(3, 13, 19), MSTORE(0, address), RETURN(0, 32)
+        let bytecode = hex_to_bytes(&format!(
+            "0x72{INIT_CODE}6000526013600d6003f060005260206000f3"
+        ))
+        .unwrap();
+        let context = CallContext {
+            address: factory,
+            ..Default::default()
+        };
+
+        let result = evm.execute_with_context(&bytecode, context);
+        assert!(result.is_success());
+        let created = create_address(factory, 5);
+        assert_eq!(result.output(), address_to_u256(created).to_big_endian());
+        assert_eq!(
+            evm.state().code(&created),
+            hex_to_bytes(RUNTIME_CODE).unwrap()
+        );
+        assert_eq!(evm.balance(created), U256::from(3));
+        assert_eq!(evm.state().nonce(&factory), 6);
+    }
+
+    #[test]
+    fn test_create2_collision() {
+        let mut evm = Evm::new();
+        // MSTORE(0, PUSH19 <initcode>), CREATE2(0, 13, 19, salt 1) twice
+        let bytecode = hex_to_bytes(&format!(
+            "0x72{INIT_CODE}60005260016013600d6000f560016013600d6000f5"
+        ))
+        .unwrap();
+
+        assert!(evm.execute(&bytecode).is_success());
+        let init_code_hash = H256::from(keccak256(&hex_to_bytes(INIT_CODE).unwrap()));
+        let created = create2_address(Address::zero(), U256::one(), init_code_hash);
+        assert_eq!(evm.stack().peek(1).unwrap(), address_to_u256(created));
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+    }
+
+    /// SELFDESTRUCT(beneficiary)
+    fn selfdestruct_code(beneficiary: Address) -> Vec<u8> {
+        hex_to_bytes(&format!("0x{}ff", push20(beneficiary))).unwrap()
+    }
+
+    #[test]
+    fn test_selfdestruct_keeps_account_since_cancun() {
+        let mut evm = Evm::new();
+        let contract = Address::repeat_byte(0xaa);
+        let beneficiary = Address::repeat_byte(0xbb);
+        evm.state_mut()
+            .set_code(contract, selfdestruct_code(beneficiary));
+        evm.set_balance(contract, U256::from(10));
+
+        let result = evm.call(CallContext {
+            address: contract,
+            ..Default::default()
+        });
+
+        assert!(result.is_success());
+        assert_eq!(evm.balance(contract), U256::zero());
+        assert_eq!(evm.balance(beneficiary), U256::from(10));
+        assert_eq!(evm.state().code(&contract), selfdestruct_code(beneficiary));
+        // A cold beneficiary that the balance brings into existence.
+        assert_eq!(
+            evm.gas().used(),
+            3 + gas::SELFDESTRUCT + gas::COLD_ACCOUNT_ACCESS + gas::NEW_ACCOUNT
+        );
+    }
+
+    #[test]
+    fn test_selfdestruct_deletes_account_before_cancun() {
+        let mut evm = Evm::new();
+        evm.set_spec(SpecId::Shanghai);
+        let contract = Address::repeat_byte(0xaa);
+        let beneficiary = Address::repeat_byte(0xbb);
+        evm.state_mut()
+            .set_code(contract, selfdestruct_code(beneficiary));
+        evm.set_balance(contract, U256::from(10));
+
+        let result = evm.call(CallContext {
+            address: contract,
+            ..Default::default()
+        });
+
+        assert!(result.is_success());
+        assert!(!evm.state().exists(&contract));
+        assert_eq!(evm.balance(beneficiary), U256::from(10));
+        assert_eq!(result.gas_refunded(), 0);
+    }
+
+    #[test]
+    fn test_checkpoint_spans_transactions() {
+        let mut evm = Evm::new();
+        evm.set_spec(SpecId::Shanghai);
+        let contract = Address::repeat_byte(0xaa);
+        let beneficiary = Address::repeat_byte(0xbb);
+        evm.state_mut()
+            .set_code(contract, selfdestruct_code(beneficiary));
+        evm.set_balance(contract, U256::from(10));
+
+        let checkpoint = evm.state().checkpoint();
+        let result = evm.call(CallContext {
+            address: contract,
+            ..Default::default()
+        });
+        assert!(result.is_success());
+        assert!(!evm.state().exists(&contract));
+        assert!(evm.state_mut().revert_to(checkpoint));
+
+        // The deletion at the end of the transaction is undone along with
+        // the transfer that preceded it.
+        assert_eq!(evm.state().code(&contract), selfdestruct_code(beneficiary));
+        assert_eq!(evm.balance(contract), U256::from(10));
+        assert!(!evm.state().exists(&beneficiary));
+        assert!(
+            evm.call(CallContext {
+                address: contract,
+                ..Default::default()
+            })
+            .is_success()
+        );
+        assert_eq!(evm.balance(beneficiary), U256::from(10));
+    }
+
+    #[test]
+    fn test_selfdestruct_refund_before_london() {
+        let mut evm = Evm::new();
+        evm.set_spec(SpecId::Berlin);
+        let bytecode = selfdestruct_code(Address::repeat_byte(0xbb));
+
+        let result = evm.execute(&bytecode);
+
+        // 24000 back, capped at half of the 7603 spent.
+        assert_eq!(result.gas_refunded(), 7603 / 2);
+    }
+
+    #[test]
+    fn test_selfdestruct_in_initcode_deletes_contract() {
+        let mut evm = Evm::new();
+        let beneficiary = Address::repeat_byte(0xbb);
+        evm.set_balance(Address::zero(), U256::from(7));
+
+        let (address, result) = evm.deploy(&selfdestruct_code(beneficiary), U256::from(7));
+
+        assert!(result.is_success());
+        assert!(!evm.state().exists(&address));
+        assert_eq!(evm.balance(beneficiary), U256::from(7));
+    }
+
+    #[test]
+    fn test_selfdestruct_in_static_context() {
+        let mut evm = Evm::new();
+        let callee = Address::repeat_byte(0xcc);
+        evm.state_mut()
+            .set_code(callee, selfdestruct_code(Address::repeat_byte(0xbb)));
+        evm.set_balance(callee, U256::from(10));
+        // STATICCALL(GAS, callee, 0, 0, 0, 0)
+        let bytecode = hex_to_bytes(&format!("0x6000600060006000{}5afa", push20(callee))).unwrap();
+
+        assert!(evm.execute(&bytecode).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::zero());
+        assert_eq!(evm.balance(callee), U256::from(10));
+    }
+
+    #[test]
+    fn test_deploy_rejects_ef_prefix() {
+        let mut evm = Evm::new();
+        // MSTORE8(0, 0xef), RETURN(0, 1)
+        let init_code = hex_to_bytes("0x60ef60005360016000f3").unwrap();
+        let (address, result) = evm.deploy(&init_code, U256::zero());
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: EvmError::InvalidCodePrefix
+            }
+        );
+        assert!(evm.state().code(&address).is_empty());
+    }
+
+    #[test]
+    fn test_ef_prefix_allowed_before_london() {
+        let mut evm = Evm::new();
+        evm.set_spec(SpecId::Berlin);
+        // MSTORE8(0, 0xef), RETURN(0, 1)
+        let init_code = hex_to_bytes("0x60ef60005360016000f3").unwrap();
+        let (address, result) = evm.deploy(&init_code, U256::zero());
+        assert!(result.is_success());
+        assert_eq!(evm.state().code(&address), &[0xef]);
+    }
+
+    #[test]
+    fn test_deploy_code_size_limit() {
+        let mut evm = Evm::new();
+        // RETURN(0, 0x6001)
+        let init_code = hex_to_bytes("0x6160016000f3").unwrap();
+        let (_, result) = evm.deploy(&init_code, U256::zero());
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: EvmError::CodeSizeExceeded
+            }
+        );
+
+        let (_, result) = evm.deploy(&vec![0; MAX_INITCODE_SIZE + 1], U256::zero());
+        assert_eq!(
+            result,
+            ExecutionResult::Halt {
+                reason: EvmError::InitcodeSizeExceeded
+            }
+        );
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        let mut evm = Evm::new();
+        let contract = Address::repeat_byte(0xaa);
+        // PUSH1 0x00, SLOAD, PUSH1 0x01, ADD, PUSH1 0x00, SSTORE,
+        // CALL(GAS, ADDRESS, 0, 0, 0, 0, 0)
+        evm.state_mut().set_code(
+            contract,
+            hex_to_bytes("0x60005460010160005560006000600060006000305af1").unwrap(),
+        );
+
+        let result = evm.call(CallContext {
+            address: contract,
+            ..Default::default()
+        });
+
+        assert!(result.is_success());
+        // The root frame plus 1024 nested calls.
+        assert_eq!(
+            evm.state().sload(&contract, &U256::zero()),
+            U256::from(CALL_DEPTH_LIMIT + 1)
+        );
+    }
+
+    #[test]
+    fn test_gas_opcode() {
+        let mut evm = Evm::new();
+        // GAS
+        let bytecode = hex_to_bytes("0x5a").unwrap();
+        assert!(evm.execute_with_gas(&bytecode, 100).is_success());
+        assert_eq!(evm.stack_top().unwrap(), U256::from(98));
+    }
 }
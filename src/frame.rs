@@ -0,0 +1,121 @@
+use crate::analysis::JumpTable;
+use crate::context::{Address, CallContext};
+use crate::gas::Gas;
+use crate::journal::Checkpoint;
+use crate::memory::Memory;
+use crate::stack::Stack;
+use primitive_types::U256;
+
+/// Maximum depth of nested message calls; the root frame is at depth 0.
+pub const CALL_DEPTH_LIMIT: usize = 1024;
+
+/// Largest runtime code a contract may be deployed with (EIP-170).
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
+/// Largest initcode accepted by contract creation (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+}
+
+/// A message call requested by one of the CALL-family opcodes, ready to be
+/// turned into a child frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallInputs {
+    pub kind: CallKind,
+    /// Account whose code the child frame runs.
+    pub code_address: Address,
+    /// Context of the child frame; `gas_limit` includes any call stipend.
+    pub context: CallContext,
+    /// Wei moved from `context.caller` to `context.address` on entry.
+    pub transfer_value: U256,
+    pub is_static: bool,
+    /// Memory range of the caller that receives the child's output.
+    pub return_offset: U256,
+    pub return_size: usize,
+}
+
+/// How the address of a new contract is derived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateScheme {
+    /// From the creator's address and nonce.
+    Create,
+    /// From the creator's address, a salt and the initcode (EIP-1014).
+    Create2 { salt: U256 },
+}
+
+/// A contract creation requested by CREATE or CREATE2, ready to be turned
+/// into a child frame running the initcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateInputs {
+    pub scheme: CreateScheme,
+    pub caller: Address,
+    /// Wei moved from `caller` to the new contract.
+    pub value: U256,
+    pub init_code: Vec<u8>,
+    pub gas_limit: u64,
+}
+
+/// One level of the call stack: the code being run together with its own
+/// stack, memory, gas and program counter.
+#[derive(Debug, Default)]
+pub struct Frame {
+    pub(crate) stack: Stack,
+    pub(crate) memory: Memory,
+    pub(crate) context: CallContext,
+    pub(crate) code: Vec<u8>,
+    pub(crate) jump_table: JumpTable,
+    pub(crate) gas: Gas,
+    pub(crate) output: Vec<u8>,
+    /// Output of the most recent call made by this frame.
+    pub(crate) return_data: Vec<u8>,
+    pub(crate) pc: usize,
+    pub(crate) running: bool,
+    pub(crate) reverted: bool,
+    pub(crate) is_static: bool,
+    pub(crate) depth: usize,
+    /// State before this frame started, restored if the frame fails.
+    pub(crate) checkpoint: Checkpoint,
+    pub(crate) return_offset: U256,
+    pub(crate) return_size: usize,
+    /// Call requested by the last executed opcode, if any.
+    pub(crate) pending_call: Option<CallInputs>,
+    /// Creation requested by the last executed opcode, if any.
+    pub(crate) pending_create: Option<CreateInputs>,
+    /// Address of the contract whose initcode this frame runs.
+    pub(crate) created_address: Option<Address>,
+    /// Precompile run in place of `code`, when the callee is one.
+    pub(crate) precompile: Option<Address>,
+}
+
+impl Frame {
+    pub fn new(code: Vec<u8>, context: CallContext, is_static: bool, depth: usize) -> Self {
+        Self {
+            stack: Stack::new(),
+            memory: Memory::new(),
+            jump_table: JumpTable::analyze(&code),
+            gas: Gas::new(context.gas_limit),
+            context,
+            code,
+            output: Vec::new(),
+            return_data: Vec::new(),
+            pc: 0,
+            running: true,
+            reverted: false,
+            is_static,
+            depth,
+            checkpoint: Checkpoint::default(),
+            return_offset: U256::zero(),
+            return_size: 0,
+            pending_call: None,
+            pending_create: None,
+            created_address: None,
+            precompile: None,
+        }
+    }
+}
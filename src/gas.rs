@@ -0,0 +1,601 @@
+use crate::error::{EvmError, Result};
+use crate::opcodes;
+use crate::spec::SpecId;
+use primitive_types::U256;
+
+pub const ZERO: u64 = 0;
+pub const JUMPDEST: u64 = 1;
+pub const BASE: u64 = 2;
+pub const VERYLOW: u64 = 3;
+pub const LOW: u64 = 5;
+pub const MID: u64 = 8;
+pub const HIGH: u64 = 10;
+pub const EXP: u64 = 10;
+pub const EXP_BYTE: u64 = 50;
+pub const EXP_BYTE_FRONTIER: u64 = 10;
+pub const KECCAK256: u64 = 30;
+pub const KECCAK256_WORD: u64 = 6;
+pub const LOG: u64 = 375;
+pub const LOG_TOPIC: u64 = 375;
+pub const LOG_DATA: u64 = 8;
+pub const MEMORY: u64 = 3;
+pub const QUAD_COEFF_DIV: u64 = 512;
+pub const COPY: u64 = 3;
+pub const BALANCE: u64 = 700;
+pub const BALANCE_FRONTIER: u64 = 20;
+pub const BALANCE_TANGERINE: u64 = 400;
+pub const EXTCODE: u64 = 700;
+pub const EXTCODE_FRONTIER: u64 = 20;
+pub const EXTCODEHASH: u64 = 700;
+pub const EXTCODEHASH_CONSTANTINOPLE: u64 = 400;
+pub const SLOAD: u64 = 800;
+pub const SLOAD_FRONTIER: u64 = 50;
+pub const SLOAD_TANGERINE: u64 = 200;
+pub const WARM_STORAGE_READ: u64 = 100;
+pub const COLD_SLOAD: u64 = 2100;
+pub const COLD_ACCOUNT_ACCESS: u64 = 2600;
+pub const CALL: u64 = 700;
+pub const CALL_FRONTIER: u64 = 40;
+pub const CALL_VALUE: u64 = 9000;
+pub const CALL_STIPEND: u64 = 2300;
+pub const NEW_ACCOUNT: u64 = 25000;
+pub const CREATE: u64 = 32000;
+pub const INITCODE_WORD: u64 = 2;
+pub const CODE_DEPOSIT: u64 = 200;
+pub const SELFDESTRUCT: u64 = 5000;
+pub const SELFDESTRUCT_REFUND: i64 = 24000;
+pub const SSTORE_SET: u64 = 20000;
+pub const SSTORE_RESET: u64 = 5000;
+pub const SSTORE_CLEARS_REFUND: u64 = 4800;
+pub const SSTORE_CLEARS_REFUND_FRONTIER: u64 = 15000;
+/// Refunds may cover at most this fraction of the gas used (EIP-3529).
+pub const MAX_REFUND_QUOTIENT: u64 = 5;
+pub const MAX_REFUND_QUOTIENT_FRONTIER: u64 = 2;
+
+/// Gas accounting for a single execution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Gas {
+    limit: u64,
+    remaining: u64,
+}
+
+impl Gas {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            remaining: limit,
+        }
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    pub fn used(&self) -> u64 {
+        self.limit - self.remaining
+    }
+
+    /// Deducts `cost` from the remaining gas. Running out of gas consumes
+    /// everything that was left.
+    pub fn charge(&mut self, cost: u64) -> Result<()> {
+        if cost > self.remaining {
+            self.remaining = 0;
+            return Err(EvmError::OutOfGas);
+        }
+        self.remaining -= cost;
+        Ok(())
+    }
+
+    /// Gives back gas that was set aside for a call but not spent by it.
+    pub fn return_unused(&mut self, amount: u64) {
+        self.remaining += amount;
+    }
+
+    pub fn consume_all(&mut self) {
+        self.remaining = 0;
+    }
+}
+
+/// Static gas cost of every opcode in the latest fork, charged before the
+/// opcode executes. Opcodes with purely dynamic pricing (or that are
+/// undefined) cost zero here.
+const STATIC_COSTS: [u64; 256] = {
+    let mut table = [ZERO; 256];
+
+    table[opcodes::ADD as usize] = VERYLOW;
+    table[opcodes::MUL as usize] = LOW;
+    table[opcodes::SUB as usize] = VERYLOW;
+    table[opcodes::DIV as usize] = LOW;
+    table[opcodes::SDIV as usize] = LOW;
+    table[opcodes::MOD as usize] = LOW;
+    table[opcodes::SMOD as usize] = LOW;
+    table[opcodes::ADDMOD as usize] = MID;
+    table[opcodes::MULMOD as usize] = MID;
+    table[opcodes::EXP as usize] = EXP;
+    table[opcodes::SIGNEXTEND as usize] = LOW;
+
+    table[opcodes::LT as usize] = VERYLOW;
+    table[opcodes::GT as usize] = VERYLOW;
+    table[opcodes::SLT as usize] = VERYLOW;
+    table[opcodes::SGT as usize] = VERYLOW;
+    table[opcodes::EQ as usize] = VERYLOW;
+    table[opcodes::ISZERO as usize] = VERYLOW;
+
+    table[opcodes::AND as usize] = VERYLOW;
+    table[opcodes::OR as usize] = VERYLOW;
+    table[opcodes::XOR as usize] = VERYLOW;
+    table[opcodes::NOT as usize] = VERYLOW;
+    table[opcodes::BYTE as usize] = VERYLOW;
+    table[opcodes::SHL as usize] = VERYLOW;
+    table[opcodes::SHR as usize] = VERYLOW;
+    table[opcodes::SAR as usize] = VERYLOW;
+
+    table[opcodes::KECCAK256 as usize] = KECCAK256;
+
+    table[opcodes::ADDRESS as usize] = BASE;
+    table[opcodes::BALANCE as usize] = WARM_STORAGE_READ;
+    table[opcodes::ORIGIN as usize] = BASE;
+    table[opcodes::CALLER as usize] = BASE;
+    table[opcodes::CALLVALUE as usize] = BASE;
+    table[opcodes::CALLDATALOAD as usize] = VERYLOW;
+    table[opcodes::CALLDATASIZE as usize] = BASE;
+    table[opcodes::CALLDATACOPY as usize] = VERYLOW;
+    table[opcodes::CODESIZE as usize] = BASE;
+    table[opcodes::CODECOPY as usize] = VERYLOW;
+    table[opcodes::GASPRICE as usize] = BASE;
+    table[opcodes::EXTCODESIZE as usize] = WARM_STORAGE_READ;
+    table[opcodes::EXTCODECOPY as usize] = WARM_STORAGE_READ;
+    table[opcodes::RETURNDATASIZE as usize] = BASE;
+    table[opcodes::RETURNDATACOPY as usize] = VERYLOW;
+    table[opcodes::EXTCODEHASH as usize] = WARM_STORAGE_READ;
+
+    table[opcodes::COINBASE as usize] = BASE;
+    table[opcodes::TIMESTAMP as usize] = BASE;
+    table[opcodes::NUMBER as usize] = BASE;
+    table[opcodes::PREVRANDAO as usize] = BASE;
+    table[opcodes::GASLIMIT as usize] = BASE;
+    table[opcodes::CHAINID as usize] = BASE;
+    table[opcodes::SELFBALANCE as usize] = LOW;
+    table[opcodes::BASEFEE as usize] = BASE;
+    table[opcodes::BLOBBASEFEE as usize] = BASE;
+
+    table[opcodes::POP as usize] = BASE;
+    table[opcodes::MLOAD as usize] = VERYLOW;
+    table[opcodes::MSTORE as usize] = VERYLOW;
+    table[opcodes::MSTORE8 as usize] = VERYLOW;
+    table[opcodes::TLOAD as usize] = WARM_STORAGE_READ;
+    table[opcodes::TSTORE as usize] = WARM_STORAGE_READ;
+    table[opcodes::JUMP as usize] = MID;
+    table[opcodes::JUMPI as usize] = HIGH;
+    table[opcodes::PC as usize] = BASE;
+    table[opcodes::MSIZE as usize] = BASE;
+    table[opcodes::GAS as usize] = BASE;
+    table[opcodes::JUMPDEST as usize] = JUMPDEST;
+    table[opcodes::MCOPY as usize] = VERYLOW;
+
+    table[opcodes::CREATE as usize] = CREATE;
+    table[opcodes::CALL as usize] = WARM_STORAGE_READ;
+    table[opcodes::CALLCODE as usize] = WARM_STORAGE_READ;
+    table[opcodes::DELEGATECALL as usize] = WARM_STORAGE_READ;
+    table[opcodes::CREATE2 as usize] = CREATE;
+    table[opcodes::STATICCALL as usize] = WARM_STORAGE_READ;
+    table[opcodes::SELFDESTRUCT as usize] = SELFDESTRUCT;
+
+    table[opcodes::PUSH0 as usize] = BASE;
+    let mut op = opcodes::PUSH1;
+    while op <= opcodes::SWAP16 {
+        // PUSH1..PUSH32, DUP1..DUP16 and SWAP1..SWAP16 are contiguous.
+        table[op as usize] = VERYLOW;
+        op += 1;
+    }
+
+    let mut topics = 0;
+    while topics <= 4 {
+        table[(opcodes::LOG0 + topics) as usize] = LOG + LOG_TOPIC * topics as u64;
+        topics += 1;
+    }
+
+    table
+};
+
+/// Static gas cost of `opcode` under `spec`: the state access opcodes were
+/// repriced by EIP-150, EIP-1884 and EIP-2929, everything else comes from
+/// the table.
+pub fn static_cost(spec: SpecId, opcode: u8) -> u64 {
+    let by_fork = |frontier, tangerine, istanbul| {
+        if spec < SpecId::Tangerine {
+            frontier
+        } else if spec < SpecId::Istanbul {
+            tangerine
+        } else if spec < SpecId::Berlin {
+            istanbul
+        } else {
+            STATIC_COSTS[opcode as usize]
+        }
+    };
+    match opcode {
+        opcodes::BALANCE => by_fork(BALANCE_FRONTIER, BALANCE_TANGERINE, BALANCE),
+        opcodes::EXTCODESIZE | opcodes::EXTCODECOPY => by_fork(EXTCODE_FRONTIER, EXTCODE, EXTCODE),
+        opcodes::EXTCODEHASH => by_fork(ZERO, EXTCODEHASH_CONSTANTINOPLE, EXTCODEHASH),
+        opcodes::SLOAD => by_fork(SLOAD_FRONTIER, SLOAD_TANGERINE, SLOAD),
+        opcodes::CALL | opcodes::CALLCODE | opcodes::DELEGATECALL | opcodes::STATICCALL => {
+            by_fork(CALL_FRONTIER, CALL, CALL)
+        }
+        opcodes::SELFDESTRUCT => by_fork(ZERO, SELFDESTRUCT, SELFDESTRUCT),
+        _ => STATIC_COSTS[opcode as usize],
+    }
+}
+
+/// Dynamic part of EXP: a fixed cost per byte of the exponent, raised by
+/// EIP-160.
+pub fn exp_cost(spec: SpecId, exponent: U256) -> u64 {
+    let bytes = exponent.bits().div_ceil(8) as u64;
+    let byte_cost = if spec >= SpecId::SpuriousDragon {
+        EXP_BYTE
+    } else {
+        EXP_BYTE_FRONTIER
+    };
+    byte_cost * bytes
+}
+
+/// Total cost of a memory of `words` 32-byte words: linear up to a point,
+/// then dominated by the quadratic term.
+pub fn memory_cost(words: u64) -> u64 {
+    MEMORY * words + words * words / QUAD_COEFF_DIV
+}
+
+/// Dynamic part of KECCAK256: a fixed cost per word hashed.
+pub fn keccak256_cost(size: usize) -> u64 {
+    KECCAK256_WORD * size.div_ceil(32) as u64
+}
+
+/// Dynamic part of LOG0..LOG4: a fixed cost per byte of data.
+pub fn log_data_cost(size: usize) -> u64 {
+    LOG_DATA * size as u64
+}
+
+/// Per-word cost of copying `size` bytes (CALLDATACOPY, MCOPY, ...).
+pub fn copy_cost(size: usize) -> u64 {
+    COPY * size.div_ceil(32) as u64
+}
+
+/// Extra cost of a call that moves value, or brings a new account into
+/// existence.
+pub fn call_extra_cost(transfers_value: bool, creates_account: bool) -> u64 {
+    let mut cost = 0;
+    if transfers_value {
+        cost += CALL_VALUE;
+    }
+    if creates_account {
+        cost += NEW_ACCOUNT;
+    }
+    cost
+}
+
+/// Gas forwarded to a call: what was asked for, capped at all but one 64th
+/// of what is left since EIP-150. Before that the caller had to be able to
+/// pay for all of it.
+pub fn call_gas_limit(spec: SpecId, remaining: u64, requested: U256) -> u64 {
+    let available = if spec >= SpecId::Tangerine {
+        remaining - remaining / 64
+    } else {
+        u64::MAX
+    };
+    if requested < U256::from(available) {
+        requested.as_u64()
+    } else {
+        available
+    }
+}
+
+/// Gas forwarded to contract creation: all of it, or all but one 64th
+/// since EIP-150.
+pub fn create_gas_limit(spec: SpecId, remaining: u64) -> u64 {
+    if spec >= SpecId::Tangerine {
+        remaining - remaining / 64
+    } else {
+        remaining
+    }
+}
+
+/// Dynamic part of CREATE and CREATE2: a fixed cost per word of initcode
+/// since EIP-3860, plus hashing it for CREATE2's address.
+pub fn create_cost(spec: SpecId, init_code_size: usize, hashes_init_code: bool) -> u64 {
+    let words = init_code_size.div_ceil(32) as u64;
+    let mut cost = 0;
+    if spec >= SpecId::Shanghai {
+        cost += INITCODE_WORD * words;
+    }
+    if hashes_init_code {
+        cost += KECCAK256_WORD * words;
+    }
+    cost
+}
+
+/// Cost of storing the runtime code returned by initcode.
+pub fn code_deposit_cost(code_size: usize) -> u64 {
+    CODE_DEPOSIT * code_size as u64
+}
+
+/// Dynamic part of SELFDESTRUCT: bringing the beneficiary into existence
+/// since EIP-150, and the first access to it since EIP-2929. A cold
+/// beneficiary pays the full cold cost, as SELFDESTRUCT has no warm base.
+pub fn selfdestruct_cost(spec: SpecId, is_cold: bool, creates_account: bool) -> u64 {
+    let mut cost = 0;
+    if creates_account && spec >= SpecId::Tangerine {
+        cost += NEW_ACCOUNT;
+    }
+    if is_cold && spec >= SpecId::Berlin {
+        cost += COLD_ACCOUNT_ACCESS;
+    }
+    cost
+}
+
+/// Refund for destroying an account, removed by EIP-3529.
+pub fn selfdestruct_refund(spec: SpecId) -> i64 {
+    if spec >= SpecId::London {
+        0
+    } else {
+        SELFDESTRUCT_REFUND
+    }
+}
+
+/// Extra cost of the first access to an account in a transaction, on top
+/// of the warm cost every access pays (EIP-2929).
+pub fn cold_account_cost(spec: SpecId, is_cold: bool) -> u64 {
+    if is_cold && spec >= SpecId::Berlin {
+        COLD_ACCOUNT_ACCESS - WARM_STORAGE_READ
+    } else {
+        0
+    }
+}
+
+/// Dynamic part of SLOAD: expensive for the first access to a slot in a
+/// transaction, cheap afterwards (EIP-2929). Earlier forks only charge the
+/// static cost.
+pub fn sload_cost(spec: SpecId, is_cold: bool) -> u64 {
+    match spec {
+        _ if spec < SpecId::Berlin => 0,
+        _ if is_cold => COLD_SLOAD,
+        _ => WARM_STORAGE_READ,
+    }
+}
+
+/// Cost of an SSTORE that doesn't change the slot, or changes it again,
+/// under net gas metering.
+fn sstore_noop_cost(spec: SpecId) -> u64 {
+    if spec >= SpecId::Berlin {
+        WARM_STORAGE_READ
+    } else if spec >= SpecId::Istanbul {
+        SLOAD
+    } else {
+        SLOAD_TANGERINE
+    }
+}
+
+/// Cost of the first change to a non-zero slot.
+fn sstore_reset_cost(spec: SpecId) -> u64 {
+    if spec >= SpecId::Berlin {
+        SSTORE_RESET - COLD_SLOAD
+    } else {
+        SSTORE_RESET
+    }
+}
+
+fn sstore_clears_refund(spec: SpecId) -> i64 {
+    if spec >= SpecId::London {
+        SSTORE_CLEARS_REFUND as i64
+    } else {
+        SSTORE_CLEARS_REFUND_FRONTIER as i64
+    }
+}
+
+/// Cost of SSTORE. Under net gas metering (EIP-1283, EIP-2200) only the
+/// first change to a slot in a transaction is expensive, setting a zero
+/// slot most of all; before it every write is either a set or a reset. A
+/// cold slot also pays for loading it (EIP-2929).
+pub fn sstore_cost(spec: SpecId, original: U256, current: U256, new: U256, is_cold: bool) -> u64 {
+    let cost = if !spec.has_net_gas_metering() {
+        if current.is_zero() && !new.is_zero() {
+            SSTORE_SET
+        } else {
+            SSTORE_RESET
+        }
+    } else if current == new || original != current {
+        sstore_noop_cost(spec)
+    } else if original.is_zero() {
+        SSTORE_SET
+    } else {
+        sstore_reset_cost(spec)
+    };
+    if is_cold && spec >= SpecId::Berlin {
+        cost + COLD_SLOAD
+    } else {
+        cost
+    }
+}
+
+/// Change to the refund counter made by SSTORE: clearing a slot earns a
+/// refund and, under net gas metering, restoring a slot's original value
+/// refunds what its first change cost beyond a no-op.
+pub fn sstore_refund(spec: SpecId, original: U256, current: U256, new: U256) -> i64 {
+    let clears = sstore_clears_refund(spec);
+    if !spec.has_net_gas_metering() {
+        return if !current.is_zero() && new.is_zero() {
+            clears
+        } else {
+            0
+        };
+    }
+    if current == new {
+        return 0;
+    }
+    if original == current {
+        return if !original.is_zero() && new.is_zero() {
+            clears
+        } else {
+            0
+        };
+    }
+
+    let mut refund = 0;
+    if !original.is_zero() {
+        if current.is_zero() {
+            refund -= clears;
+        } else if new.is_zero() {
+            refund += clears;
+        }
+    }
+    if original == new {
+        let first_change = if original.is_zero() {
+            SSTORE_SET
+        } else {
+            sstore_reset_cost(spec)
+        };
+        refund += (first_change - sstore_noop_cost(spec)) as i64;
+    }
+    refund
+}
+
+/// Part of the refund counter actually paid back to the transaction: at
+/// most half the gas used, or a fifth since EIP-3529.
+pub fn capped_refund(spec: SpecId, refund: u64, gas_used: u64) -> u64 {
+    let quotient = if spec >= SpecId::London {
+        MAX_REFUND_QUOTIENT
+    } else {
+        MAX_REFUND_QUOTIENT_FRONTIER
+    };
+    refund.min(gas_used / quotient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charge() {
+        let mut gas = Gas::new(10);
+        gas.charge(3).unwrap();
+        assert_eq!(gas.remaining(), 7);
+        assert_eq!(gas.used(), 3);
+    }
+
+    #[test]
+    fn test_out_of_gas_consumes_all() {
+        let mut gas = Gas::new(10);
+        assert_eq!(gas.charge(11), Err(EvmError::OutOfGas));
+        assert_eq!(gas.remaining(), 0);
+        assert_eq!(gas.used(), 10);
+    }
+
+    const LATEST: SpecId = SpecId::Prague;
+
+    #[test]
+    fn test_static_costs() {
+        assert_eq!(static_cost(LATEST, opcodes::STOP), 0);
+        assert_eq!(static_cost(LATEST, opcodes::ADD), 3);
+        assert_eq!(static_cost(LATEST, opcodes::PUSH32), 3);
+        assert_eq!(static_cost(LATEST, opcodes::SWAP16), 3);
+        assert_eq!(static_cost(LATEST, opcodes::JUMPI), 10);
+        assert_eq!(static_cost(LATEST, opcodes::LOG0), 375);
+        assert_eq!(static_cost(LATEST, opcodes::LOG4), 375 * 5);
+    }
+
+    #[test]
+    fn test_static_costs_by_fork() {
+        assert_eq!(static_cost(SpecId::Frontier, opcodes::SLOAD), 50);
+        assert_eq!(static_cost(SpecId::Tangerine, opcodes::SLOAD), 200);
+        assert_eq!(static_cost(SpecId::Istanbul, opcodes::SLOAD), 800);
+        assert_eq!(static_cost(SpecId::Berlin, opcodes::SLOAD), 0);
+        assert_eq!(static_cost(SpecId::Homestead, opcodes::CALL), 40);
+        assert_eq!(static_cost(SpecId::Byzantium, opcodes::BALANCE), 400);
+        assert_eq!(static_cost(SpecId::Petersburg, opcodes::EXTCODEHASH), 400);
+        assert_eq!(static_cost(SpecId::London, opcodes::EXTCODECOPY), 100);
+        assert_eq!(static_cost(SpecId::Homestead, opcodes::SELFDESTRUCT), 0);
+        assert_eq!(static_cost(SpecId::Tangerine, opcodes::SELFDESTRUCT), 5000);
+    }
+
+    #[test]
+    fn test_exp_cost() {
+        assert_eq!(exp_cost(LATEST, U256::zero()), 0);
+        assert_eq!(exp_cost(LATEST, U256::from(0xff)), 50);
+        assert_eq!(exp_cost(LATEST, U256::from(0x100)), 100);
+        assert_eq!(exp_cost(LATEST, U256::MAX), 1600);
+        assert_eq!(exp_cost(SpecId::Homestead, U256::MAX), 320);
+    }
+
+    #[test]
+    fn test_call_gas_limit() {
+        assert_eq!(call_gas_limit(LATEST, 6400, U256::from(1000)), 1000);
+        assert_eq!(call_gas_limit(LATEST, 6400, U256::MAX), 6300);
+        assert_eq!(
+            call_gas_limit(SpecId::Homestead, 6400, U256::from(7000)),
+            7000
+        );
+        assert_eq!(create_gas_limit(LATEST, 6400), 6300);
+        assert_eq!(create_gas_limit(SpecId::Homestead, 6400), 6400);
+    }
+
+    #[test]
+    fn test_access_costs() {
+        let balance = static_cost(LATEST, opcodes::BALANCE);
+        assert_eq!(balance + cold_account_cost(LATEST, true), 2600);
+        assert_eq!(balance + cold_account_cost(LATEST, false), 100);
+        let sload = static_cost(LATEST, opcodes::SLOAD);
+        assert_eq!(sload + sload_cost(LATEST, true), 2100);
+        assert_eq!(sload + sload_cost(LATEST, false), 100);
+        assert_eq!(cold_account_cost(SpecId::Istanbul, true), 0);
+        assert_eq!(sload_cost(SpecId::Istanbul, true), 0);
+    }
+
+    #[test]
+    fn test_selfdestruct_cost() {
+        assert_eq!(selfdestruct_cost(LATEST, true, true), 25000 + 2600);
+        assert_eq!(selfdestruct_cost(LATEST, false, false), 0);
+        assert_eq!(selfdestruct_cost(SpecId::Istanbul, true, true), 25000);
+        assert_eq!(selfdestruct_cost(SpecId::Homestead, true, true), 0);
+        assert_eq!(selfdestruct_refund(SpecId::Berlin), 24000);
+        assert_eq!(selfdestruct_refund(SpecId::London), 0);
+    }
+
+    #[test]
+    fn test_sstore_cost() {
+        let [zero, one, two] = [U256::zero(), U256::one(), U256::from(2)];
+        assert_eq!(sstore_cost(LATEST, zero, zero, one, true), 22100);
+        assert_eq!(sstore_cost(LATEST, one, one, two, true), 5000);
+        assert_eq!(sstore_cost(LATEST, one, one, two, false), 2900);
+        assert_eq!(sstore_cost(LATEST, one, one, one, false), 100);
+        assert_eq!(sstore_cost(LATEST, zero, one, two, false), 100);
+        assert_eq!(sstore_cost(SpecId::Istanbul, one, one, one, false), 800);
+        assert_eq!(
+            sstore_cost(SpecId::Constantinople, zero, one, two, false),
+            200
+        );
+        assert_eq!(sstore_cost(SpecId::Petersburg, zero, one, two, false), 5000);
+    }
+
+    #[test]
+    fn test_sstore_refund() {
+        let [zero, one, two] = [U256::zero(), U256::one(), U256::from(2)];
+        assert_eq!(sstore_refund(LATEST, one, one, zero), 4800);
+        assert_eq!(sstore_refund(LATEST, one, zero, one), -4800 + 2800);
+        assert_eq!(sstore_refund(LATEST, one, two, zero), 4800);
+        assert_eq!(sstore_refund(LATEST, zero, one, zero), 19900);
+        assert_eq!(sstore_refund(LATEST, zero, one, two), 0);
+        assert_eq!(sstore_refund(SpecId::Istanbul, zero, one, zero), 19200);
+        assert_eq!(sstore_refund(SpecId::Petersburg, zero, one, zero), 15000);
+        assert_eq!(capped_refund(LATEST, 19900, 20112), 4022);
+        assert_eq!(capped_refund(SpecId::Berlin, 19900, 20112), 10056);
+    }
+
+    #[test]
+    fn test_memory_cost() {
+        assert_eq!(memory_cost(0), 0);
+        assert_eq!(memory_cost(1), 3);
+        assert_eq!(memory_cost(1024), 3 * 1024 + 2048);
+    }
+}
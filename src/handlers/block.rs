@@ -0,0 +1,50 @@
+use crate::context::BlockEnv;
+use crate::error::{EvmError, Result};
+use crate::opcodes;
+use crate::stack::Stack;
+use crate::utils::address_to_u256;
+use primitive_types::U256;
+
+pub fn handle_block_info(opcode: u8, block: &BlockEnv, stack: &mut Stack) -> Result<()> {
+    let value = match opcode {
+        opcodes::COINBASE => address_to_u256(block.coinbase),
+        opcodes::TIMESTAMP => block.timestamp,
+        opcodes::NUMBER => block.number,
+        opcodes::PREVRANDAO => block.prevrandao,
+        opcodes::GASLIMIT => U256::from(block.gas_limit),
+        opcodes::CHAINID => U256::from(block.chain_id),
+        opcodes::BASEFEE => block.basefee,
+        opcodes::BLOBBASEFEE => block.blob_basefee,
+        _ => return Err(EvmError::InvalidOpcode(opcode)),
+    };
+    stack.push(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Address;
+
+    #[test]
+    fn test_block_info() {
+        let block = BlockEnv {
+            number: U256::from(19_000_000),
+            timestamp: U256::from(1_700_000_000),
+            coinbase: Address::repeat_byte(0xcb),
+            ..Default::default()
+        };
+        let mut stack = Stack::new();
+
+        handle_block_info(opcodes::NUMBER, &block, &mut stack).unwrap();
+        assert_eq!(stack.pop().unwrap(), U256::from(19_000_000));
+
+        handle_block_info(opcodes::TIMESTAMP, &block, &mut stack).unwrap();
+        assert_eq!(stack.pop().unwrap(), U256::from(1_700_000_000));
+
+        handle_block_info(opcodes::COINBASE, &block, &mut stack).unwrap();
+        assert_eq!(stack.pop().unwrap(), U256::from_big_endian(&[0xcb; 20]));
+
+        handle_block_info(opcodes::CHAINID, &block, &mut stack).unwrap();
+        assert_eq!(stack.pop().unwrap(), U256::one());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::analysis::JumpTable;
+use crate::error::{EvmError, Result};
+use crate::stack::Stack;
+use primitive_types::U256;
+
+pub fn handle_jump(stack: &mut Stack, jump_table: &JumpTable, pc: &mut usize) -> Result<()> {
+    let dest = stack.pop()?;
+    *pc = jump_target(dest, jump_table)?;
+    Ok(())
+}
+
+pub fn handle_jumpi(stack: &mut Stack, jump_table: &JumpTable, pc: &mut usize) -> Result<()> {
+    let dest = stack.pop()?;
+    let condition = stack.pop()?;
+    if !condition.is_zero() {
+        *pc = jump_target(dest, jump_table)?;
+    }
+    Ok(())
+}
+
+pub fn handle_pc(stack: &mut Stack, pc: usize) -> Result<()> {
+    // `pc` has already been advanced past the PC opcode itself.
+    stack.push(U256::from(pc - 1))
+}
+
+fn jump_target(dest: U256, jump_table: &JumpTable) -> Result<usize> {
+    if dest > U256::from(usize::MAX) || !jump_table.is_valid(dest.as_usize()) {
+        return Err(EvmError::InvalidJump(dest));
+    }
+    Ok(dest.as_usize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_valid() {
+        let table = JumpTable::analyze(&[0x60, 0x03, 0x56, 0x5b]);
+        let mut stack = Stack::new();
+        let mut pc = 3usize;
+        stack.push(U256::from(3)).unwrap();
+        handle_jump(&mut stack, &table, &mut pc).unwrap();
+        assert_eq!(pc, 3);
+    }
+
+    #[test]
+    fn test_jump_invalid() {
+        let table = JumpTable::analyze(&[0x60, 0x02, 0x56]);
+        let mut stack = Stack::new();
+        let mut pc = 3usize;
+        stack.push(U256::from(2)).unwrap();
+        assert_eq!(
+            handle_jump(&mut stack, &table, &mut pc),
+            Err(EvmError::InvalidJump(U256::from(2)))
+        );
+    }
+
+    #[test]
+    fn test_jumpi_not_taken() {
+        let table = JumpTable::analyze(&[0x5b]);
+        let mut stack = Stack::new();
+        let mut pc = 7usize;
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::from(0x1234)).unwrap();
+        handle_jumpi(&mut stack, &table, &mut pc).unwrap();
+        assert_eq!(pc, 7);
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+use crate::context::{Address, CallContext};
+use crate::error::{EvmError, Result};
+use crate::gas::{Gas, cold_account_cost, copy_cost};
+use crate::memory::{Memory, as_memory_size};
+use crate::spec::SpecId;
+use crate::stack::Stack;
+use crate::state::WorldState;
+use crate::utils::{address_to_u256, slice_padded, u256_to_address};
+use primitive_types::U256;
+
+pub fn handle_address(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    stack.push(address_to_u256(context.address))
+}
+
+pub fn handle_balance(
+    spec: SpecId,
+    state: &mut WorldState,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let address = pop_accessed_address(spec, state, stack, gas)?;
+    stack.push(state.balance(&address))
+}
+
+pub fn handle_origin(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    stack.push(address_to_u256(context.origin))
+}
+
+pub fn handle_caller(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    stack.push(address_to_u256(context.caller))
+}
+
+pub fn handle_gasprice(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    stack.push(context.gas_price)
+}
+
+pub fn handle_callvalue(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    stack.push(context.value)
+}
+
+pub fn handle_calldataload(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    let offset = stack.pop()?;
+    let word = slice_padded(&context.data, offset, 32);
+    stack.push(U256::from_big_endian(&word))
+}
+
+pub fn handle_calldatasize(context: &CallContext, stack: &mut Stack) -> Result<()> {
+    stack.push(U256::from(context.data.len()))
+}
+
+pub fn handle_calldatacopy(
+    context: &CallContext,
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    copy_to_memory(&context.data, memory, stack, gas)
+}
+
+pub fn handle_codesize(bytecode: &[u8], stack: &mut Stack) -> Result<()> {
+    stack.push(U256::from(bytecode.len()))
+}
+
+pub fn handle_codecopy(
+    bytecode: &[u8],
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    copy_to_memory(bytecode, memory, stack, gas)
+}
+
+pub fn handle_extcodesize(
+    spec: SpecId,
+    state: &mut WorldState,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let address = pop_accessed_address(spec, state, stack, gas)?;
+    stack.push(U256::from(state.code(&address).len()))
+}
+
+pub fn handle_extcodecopy(
+    spec: SpecId,
+    state: &mut WorldState,
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let address = pop_accessed_address(spec, state, stack, gas)?;
+    copy_to_memory(state.code(&address), memory, stack, gas)
+}
+
+pub fn handle_extcodehash(
+    spec: SpecId,
+    state: &mut WorldState,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let address = pop_accessed_address(spec, state, stack, gas)?;
+    stack.push(U256::from_big_endian(state.code_hash(&address).as_bytes()))
+}
+
+pub fn handle_returndatasize(return_data: &[u8], stack: &mut Stack) -> Result<()> {
+    stack.push(U256::from(return_data.len()))
+}
+
+/// Like CALLDATACOPY, except that reading past the end of the return data is
+/// an error rather than zero padding (EIP-211).
+pub fn handle_returndatacopy(
+    return_data: &[u8],
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let dest_offset = stack.pop()?;
+    let offset = stack.pop()?;
+    let size = stack.pop()?;
+
+    let end = offset.checked_add(size);
+    if end.is_none_or(|end| end > U256::from(return_data.len())) {
+        return Err(EvmError::ReturnDataOutOfBounds);
+    }
+    let offset = offset.as_usize();
+    let size = size.as_usize();
+
+    gas.charge(copy_cost(size))?;
+    gas.charge(memory.expansion_cost(dest_offset, size)?)?;
+
+    memory.write_bytes(dest_offset, &return_data[offset..offset + size])
+}
+
+/// Pops an address, charging extra if this is the first time the
+/// transaction touches it (EIP-2929).
+fn pop_accessed_address(
+    spec: SpecId,
+    state: &mut WorldState,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<Address> {
+    let address = u256_to_address(stack.pop()?);
+    gas.charge(cold_account_cost(spec, state.warm_address(address)))?;
+    Ok(address)
+}
+
+/// Pops `(dest_offset, offset, size)` and copies `source[offset..offset + size]`
+/// into memory, padding with zeros past the end of `source`.
+fn copy_to_memory(
+    source: &[u8],
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let dest_offset = stack.pop()?;
+    let offset = stack.pop()?;
+    let size = as_memory_size(stack.pop()?)?;
+
+    gas.charge(copy_cost(size))?;
+    gas.charge(memory.expansion_cost(dest_offset, size)?)?;
+
+    memory.write_bytes(dest_offset, &slice_padded(source, offset, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caller() {
+        let context = CallContext {
+            caller: Address::repeat_byte(0xaa),
+            ..Default::default()
+        };
+        let mut stack = Stack::new();
+        handle_caller(&context, &mut stack).unwrap();
+        assert_eq!(stack.top().unwrap(), U256::from_big_endian(&[0xaa; 20]));
+    }
+
+    #[test]
+    fn test_calldataload_pads_with_zeros() {
+        let context = CallContext {
+            data: vec![0x11, 0x22],
+            ..Default::default()
+        };
+        let mut stack = Stack::new();
+        stack.push(U256::one()).unwrap();
+        handle_calldataload(&context, &mut stack).unwrap();
+        assert_eq!(stack.top().unwrap(), U256::from(0x22) << 248);
+
+        stack.push(U256::MAX).unwrap();
+        handle_calldataload(&context, &mut stack).unwrap();
+        assert_eq!(stack.top().unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn test_calldatacopy() {
+        let context = CallContext {
+            data: vec![1, 2, 3, 4],
+            ..Default::default()
+        };
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(4)).unwrap();
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        handle_calldatacopy(&context, &mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(
+            memory.read_bytes(U256::zero(), 4).unwrap(),
+            vec![3, 4, 0, 0]
+        );
+        // One word copied plus one word of expansion.
+        assert_eq!(gas.used(), 6);
+    }
+
+    #[test]
+    fn test_ext_code_opcodes() {
+        let mut state = WorldState::new();
+        let contract = Address::repeat_byte(0xcc);
+        state.set_code(contract, vec![0x60, 0x01, 0x00]);
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(address_to_u256(contract)).unwrap();
+        handle_extcodesize(SpecId::default(), &mut state, &mut stack, &mut gas).unwrap();
+        assert_eq!(stack.pop().unwrap(), U256::from(3));
+
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::one()).unwrap();
+        stack.push(U256::zero()).unwrap();
+        stack.push(address_to_u256(contract)).unwrap();
+        handle_extcodecopy(
+            SpecId::default(),
+            &mut state,
+            &mut memory,
+            &mut stack,
+            &mut gas,
+        )
+        .unwrap();
+        assert_eq!(
+            memory.read_bytes(U256::zero(), 2).unwrap(),
+            vec![0x01, 0x00]
+        );
+
+        stack
+            .push(address_to_u256(Address::repeat_byte(0xdd)))
+            .unwrap();
+        handle_extcodehash(SpecId::default(), &mut state, &mut stack, &mut gas).unwrap();
+        assert_eq!(stack.pop().unwrap(), U256::zero());
+
+        // Cold for the first access to each account only, and one word
+        // copied into one word of fresh memory.
+        assert_eq!(gas.used(), 2 * 2500 + 3 + 3);
+    }
+
+    #[test]
+    fn test_returndatacopy() {
+        let return_data = [1, 2, 3, 4];
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        handle_returndatacopy(&return_data, &mut memory, &mut stack, &mut gas).unwrap();
+        assert_eq!(memory.read_bytes(U256::zero(), 2).unwrap(), vec![3, 4]);
+
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::from(3)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        assert_eq!(
+            handle_returndatacopy(&return_data, &mut memory, &mut stack, &mut gas),
+            Err(EvmError::ReturnDataOutOfBounds)
+        );
+
+        stack.push(U256::one()).unwrap();
+        stack.push(U256::MAX).unwrap();
+        stack.push(U256::zero()).unwrap();
+        assert_eq!(
+            handle_returndatacopy(&return_data, &mut memory, &mut stack, &mut gas),
+            Err(EvmError::ReturnDataOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_codecopy() {
+        let bytecode = [0x60, 0x01, 0x00];
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(3)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::zero()).unwrap();
+        handle_codecopy(&bytecode, &mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(
+            memory.read_bytes(U256::zero(), 3).unwrap(),
+            bytecode.to_vec()
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::Result;
+use crate::gas::{self, Gas};
+use crate::memory::{Memory, as_memory_size};
+use crate::stack::Stack;
+use crate::utils::keccak256;
+use primitive_types::U256;
+
+pub fn handle_keccak256(memory: &mut Memory, stack: &mut Stack, gas: &mut Gas) -> Result<()> {
+    let offset = stack.pop()?;
+    let size = as_memory_size(stack.pop()?)?;
+
+    gas.charge(gas::keccak256_cost(size))?;
+    gas.charge(memory.expansion_cost(offset, size)?)?;
+
+    let data = memory.read_bytes(offset, size)?;
+    stack.push(U256::from_big_endian(&keccak256(&data)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256_empty_input() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::from(1000)).unwrap();
+        handle_keccak256(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(stack.top().unwrap(), U256::from_big_endian(&keccak256(&[])));
+        assert_eq!(memory.size(), 0);
+        assert_eq!(gas.used(), 0);
+    }
+
+    #[test]
+    fn test_keccak256_word() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+        memory.write_bytes(U256::zero(), &[0xff; 4]).unwrap();
+
+        stack.push(U256::from(4)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        handle_keccak256(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(
+            stack.top().unwrap(),
+            U256::from_big_endian(&keccak256(&[0xff; 4]))
+        );
+        assert_eq!(gas.used(), 6);
+    }
+}
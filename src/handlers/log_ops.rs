@@ -0,0 +1,91 @@
+use crate::context::Address;
+use crate::error::Result;
+use crate::gas::{Gas, log_data_cost};
+use crate::log::Log;
+use crate::memory::{Memory, as_memory_size};
+use crate::opcodes;
+use crate::stack::Stack;
+use primitive_types::H256;
+
+pub fn handle_log(
+    opcode: u8,
+    address: Address,
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<Log> {
+    let topic_count = (opcode - opcodes::LOG0) as usize;
+    let offset = stack.pop()?;
+    let size = as_memory_size(stack.pop()?)?;
+
+    gas.charge(log_data_cost(size))?;
+    gas.charge(memory.expansion_cost(offset, size)?)?;
+
+    let mut topics = Vec::with_capacity(topic_count);
+    for _ in 0..topic_count {
+        topics.push(H256::from(stack.pop()?.to_big_endian()));
+    }
+    let data = memory.read_bytes(offset, size)?;
+
+    Ok(Log {
+        address,
+        topics,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvmError;
+    use primitive_types::U256;
+
+    #[test]
+    fn test_log2() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+        memory.write_bytes(U256::zero(), &[0xab, 0xcd]).unwrap();
+
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::one()).unwrap();
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        let log = handle_log(
+            opcodes::LOG2,
+            Address::repeat_byte(0x01),
+            &mut memory,
+            &mut stack,
+            &mut gas,
+        )
+        .unwrap();
+
+        assert_eq!(log.address, Address::repeat_byte(0x01));
+        assert_eq!(
+            log.topics,
+            vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)]
+        );
+        assert_eq!(log.data, vec![0xab, 0xcd]);
+        assert_eq!(gas.used(), 16);
+    }
+
+    #[test]
+    fn test_log_missing_topic() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::zero()).unwrap();
+        assert_eq!(
+            handle_log(
+                opcodes::LOG1,
+                Address::zero(),
+                &mut memory,
+                &mut stack,
+                &mut gas
+            ),
+            Err(EvmError::StackUnderflow)
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::Result;
+use crate::gas::{Gas, copy_cost};
+use crate::memory::{Memory, as_memory_size};
+use crate::stack::Stack;
+use primitive_types::U256;
+
+pub fn handle_mload(memory: &mut Memory, stack: &mut Stack, gas: &mut Gas) -> Result<()> {
+    let offset = stack.pop()?;
+    gas.charge(memory.expansion_cost(offset, 32)?)?;
+    let word = memory.read_bytes(offset, 32)?;
+    stack.push(U256::from_big_endian(&word))?;
+    Ok(())
+}
+
+pub fn handle_mstore(memory: &mut Memory, stack: &mut Stack, gas: &mut Gas) -> Result<()> {
+    let offset = stack.pop()?;
+    let value = stack.pop()?;
+    gas.charge(memory.expansion_cost(offset, 32)?)?;
+    memory.write_bytes(offset, &value.to_big_endian())?;
+    Ok(())
+}
+
+pub fn handle_mstore8(memory: &mut Memory, stack: &mut Stack, gas: &mut Gas) -> Result<()> {
+    let offset = stack.pop()?;
+    let value = stack.pop()?;
+    gas.charge(memory.expansion_cost(offset, 1)?)?;
+    memory.write(offset, value.byte(0))?;
+    Ok(())
+}
+
+pub fn handle_msize(memory: &Memory, stack: &mut Stack) -> Result<()> {
+    stack.push(U256::from(memory.size()))
+}
+
+pub fn handle_mcopy(memory: &mut Memory, stack: &mut Stack, gas: &mut Gas) -> Result<()> {
+    let dst = stack.pop()?;
+    let src = stack.pop()?;
+    let size = as_memory_size(stack.pop()?)?;
+
+    gas.charge(copy_cost(size))?;
+    let expansion = memory
+        .expansion_cost(dst, size)?
+        .max(memory.expansion_cost(src, size)?);
+    gas.charge(expansion)?;
+
+    memory.copy(dst, src, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvmError;
+
+    #[test]
+    fn test_mstore_mload() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(0x42)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        handle_mstore(&mut memory, &mut stack, &mut gas).unwrap();
+
+        stack.push(U256::zero()).unwrap();
+        handle_mload(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(stack.top().unwrap(), U256::from(0x42));
+        assert_eq!(memory.size(), 32);
+        // Only the first access pays for the word.
+        assert_eq!(gas.used(), 3);
+    }
+
+    #[test]
+    fn test_mstore8() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(0x1234)).unwrap();
+        stack.push(U256::from(31)).unwrap();
+        handle_mstore8(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(memory.read(U256::from(31)).unwrap(), 0x34);
+        assert_eq!(memory.size(), 32);
+    }
+
+    #[test]
+    fn test_mload_unaligned_expands() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(1)).unwrap();
+        handle_mload(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(stack.top().unwrap(), U256::zero());
+        assert_eq!(memory.size(), 64);
+        assert_eq!(gas.used(), 6);
+    }
+
+    #[test]
+    fn test_mload_huge_offset() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::MAX).unwrap();
+        assert_eq!(
+            handle_mload(&mut memory, &mut stack, &mut gas),
+            Err(EvmError::MemoryOutOfBounds)
+        );
+        assert_eq!(memory.size(), 0);
+    }
+
+    #[test]
+    fn test_mstore_out_of_gas() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(1000);
+
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::from(1_000_000)).unwrap();
+        assert_eq!(
+            handle_mstore(&mut memory, &mut stack, &mut gas),
+            Err(EvmError::OutOfGas)
+        );
+        assert_eq!(memory.size(), 0);
+    }
+
+    #[test]
+    fn test_mcopy() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+        memory.write_bytes(U256::zero(), &[0xaa; 32]).unwrap();
+
+        stack.push(U256::from(32)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::from(32)).unwrap();
+        handle_mcopy(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(
+            memory.read_bytes(U256::from(32), 32).unwrap(),
+            vec![0xaa; 32]
+        );
+        assert_eq!(memory.size(), 64);
+        // One word copied plus one word of expansion.
+        assert_eq!(gas.used(), 6);
+    }
+}
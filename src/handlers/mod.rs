@@ -1,7 +1,26 @@
 pub mod arithmetic;
+pub mod block;
+pub mod control_flow;
+pub mod environment;
+pub mod hash_ops;
+pub mod log_ops;
+pub mod memory_ops;
 pub mod stack_ops;
 pub mod storage_ops;
+pub mod system;
 
 pub use arithmetic::handle_arithmetic;
+pub use block::handle_block_info;
+pub use control_flow::{handle_jump, handle_jumpi, handle_pc};
+pub use environment::{
+    handle_address, handle_balance, handle_calldatacopy, handle_calldataload, handle_calldatasize,
+    handle_caller, handle_callvalue, handle_codecopy, handle_codesize, handle_extcodecopy,
+    handle_extcodehash, handle_extcodesize, handle_gasprice, handle_origin, handle_returndatacopy,
+    handle_returndatasize,
+};
+pub use hash_ops::handle_keccak256;
+pub use log_ops::handle_log;
+pub use memory_ops::{handle_mcopy, handle_mload, handle_msize, handle_mstore, handle_mstore8};
 pub use stack_ops::{handle_dup, handle_push, handle_swap};
-pub use storage_ops::{handle_sload, handle_sstore};
+pub use storage_ops::{handle_sload, handle_sstore, handle_tload, handle_tstore};
+pub use system::{handle_call, handle_create, handle_return, handle_selfdestruct};
@@ -1,18 +1,57 @@
-use crate::error::Result;
+use crate::context::Address;
+use crate::error::{EvmError, Result};
+use crate::gas::{self, Gas, sload_cost, sstore_cost, sstore_refund};
+use crate::spec::SpecId;
 use crate::stack::Stack;
-use crate::storage::Storage;
+use crate::state::WorldState;
 
-pub fn handle_sload(storage: &Storage, stack: &mut Stack) -> Result<()> {
+pub fn handle_sload(
+    spec: SpecId,
+    state: &mut WorldState,
+    address: Address,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
     let key = stack.pop()?;
-    let value = storage.read(&key);
+    gas.charge(sload_cost(spec, state.warm_slot(address, key)))?;
+    let value = state.sload(&address, &key);
     stack.push(value)?;
     Ok(())
 }
 
-pub fn handle_sstore(storage: &mut Storage, stack: &mut Stack) -> Result<()> {
+pub fn handle_sstore(
+    spec: SpecId,
+    state: &mut WorldState,
+    address: Address,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
     let key = stack.pop()?;
     let value = stack.pop()?;
-    storage.write(key, value);
+
+    // Never let SSTORE run on the stipend of a value transfer (EIP-2200).
+    if spec >= SpecId::Istanbul && gas.remaining() <= gas::CALL_STIPEND {
+        return Err(EvmError::OutOfGas);
+    }
+
+    let is_cold = state.warm_slot(address, key);
+    let original = state.original_storage(&address, &key);
+    let current = state.sload(&address, &key);
+    gas.charge(sstore_cost(spec, original, current, value, is_cold))?;
+    state.record_refund(sstore_refund(spec, original, current, value));
+    state.sstore(address, key, value);
+    Ok(())
+}
+
+pub fn handle_tload(state: &WorldState, address: &Address, stack: &mut Stack) -> Result<()> {
+    let key = stack.pop()?;
+    stack.push(state.tload(address, &key))
+}
+
+pub fn handle_tstore(state: &mut WorldState, address: Address, stack: &mut Stack) -> Result<()> {
+    let key = stack.pop()?;
+    let value = stack.pop()?;
+    state.tstore(address, key, value);
     Ok(())
 }
 
@@ -23,16 +62,89 @@ mod tests {
 
     #[test]
     fn test_sstore_sload() {
-        let mut storage = Storage::new();
+        let mut state = WorldState::new();
         let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+        let address = Address::repeat_byte(0x01);
 
         stack.push(U256::from(100)).unwrap();
         stack.push(U256::from(1)).unwrap();
-        handle_sstore(&mut storage, &mut stack).unwrap();
+        handle_sstore(SpecId::default(), &mut state, address, &mut stack, &mut gas).unwrap();
 
         stack.push(U256::from(1)).unwrap();
-        handle_sload(&storage, &mut stack).unwrap();
+        handle_sload(SpecId::default(), &mut state, address, &mut stack, &mut gas).unwrap();
 
         assert_eq!(stack.top().unwrap(), U256::from(100));
+        // The store warms the slot for the load.
+        let gas_used =
+            crate::gas::SSTORE_SET + crate::gas::COLD_SLOAD + crate::gas::WARM_STORAGE_READ;
+        assert_eq!(gas.used(), gas_used);
+    }
+
+    #[test]
+    fn test_sstore_stipend_sentry() {
+        let mut state = WorldState::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(gas::CALL_STIPEND);
+
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::zero()).unwrap();
+        assert_eq!(
+            handle_sstore(
+                SpecId::default(),
+                &mut state,
+                Address::zero(),
+                &mut stack,
+                &mut gas
+            ),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_tstore_tload() {
+        let mut state = WorldState::new();
+        let mut stack = Stack::new();
+        let address = Address::repeat_byte(0x01);
+
+        stack.push(U256::from(100)).unwrap();
+        stack.push(U256::from(1)).unwrap();
+        handle_tstore(&mut state, address, &mut stack).unwrap();
+
+        stack.push(U256::from(1)).unwrap();
+        handle_tload(&state, &address, &mut stack).unwrap();
+
+        assert_eq!(stack.top().unwrap(), U256::from(100));
+        assert_eq!(state.sload(&address, &U256::from(1)), U256::zero());
+    }
+
+    #[test]
+    fn test_storage_scoped_to_address() {
+        let mut state = WorldState::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(U256::from(100)).unwrap();
+        stack.push(U256::from(1)).unwrap();
+        handle_sstore(
+            SpecId::default(),
+            &mut state,
+            Address::repeat_byte(0x01),
+            &mut stack,
+            &mut gas,
+        )
+        .unwrap();
+
+        stack.push(U256::from(1)).unwrap();
+        handle_sload(
+            SpecId::default(),
+            &mut state,
+            Address::repeat_byte(0x02),
+            &mut stack,
+            &mut gas,
+        )
+        .unwrap();
+
+        assert_eq!(stack.top().unwrap(), U256::zero());
     }
 }
@@ -0,0 +1,438 @@
+use crate::context::{Address, CallContext};
+use crate::error::{EvmError, Result};
+use crate::frame::{CallInputs, CallKind, CreateInputs, CreateScheme, MAX_INITCODE_SIZE};
+use crate::gas::{self, Gas};
+use crate::memory::{Memory, as_memory_size};
+use crate::opcodes;
+use crate::spec::SpecId;
+use crate::stack::Stack;
+use crate::state::{Account, WorldState};
+use crate::utils::u256_to_address;
+use primitive_types::U256;
+
+/// Shared by RETURN and REVERT: reads the output range from memory.
+pub fn handle_return(memory: &mut Memory, stack: &mut Stack, gas: &mut Gas) -> Result<Vec<u8>> {
+    let offset = stack.pop()?;
+    let size = as_memory_size(stack.pop()?)?;
+    gas.charge(memory.expansion_cost(offset, size)?)?;
+    memory.read_bytes(offset, size)
+}
+
+/// Shared by the CALL family: pops the arguments, charges everything the
+/// caller pays up front and describes the child frame to run.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_call(
+    spec: SpecId,
+    kind: CallKind,
+    context: &CallContext,
+    state: &mut WorldState,
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+    is_static: bool,
+) -> Result<CallInputs> {
+    let requested_gas = stack.pop()?;
+    let target = u256_to_address(stack.pop()?);
+    let value = match kind {
+        CallKind::Call | CallKind::CallCode => stack.pop()?,
+        CallKind::DelegateCall | CallKind::StaticCall => U256::zero(),
+    };
+    let args_offset = stack.pop()?;
+    let args_size = as_memory_size(stack.pop()?)?;
+    let return_offset = stack.pop()?;
+    let return_size = as_memory_size(stack.pop()?)?;
+
+    if kind == CallKind::Call && is_static && !value.is_zero() {
+        return Err(EvmError::StateChangeInStaticContext);
+    }
+
+    let expansion = memory
+        .expansion_cost(args_offset, args_size)?
+        .max(memory.expansion_cost(return_offset, return_size)?);
+    gas.charge(expansion)?;
+    memory.expand(return_offset, return_size)?;
+    let data = memory.read_bytes(args_offset, args_size)?;
+
+    gas.charge(gas::cold_account_cost(spec, state.warm_address(target)))?;
+    let transfers_value = !value.is_zero();
+    // Since EIP-161 only a value transfer can bring an account to life.
+    let creates_account = kind == CallKind::Call
+        && if spec >= SpecId::SpuriousDragon {
+            transfers_value && state.account(&target).is_none_or(Account::is_empty)
+        } else {
+            !state.exists(&target)
+        };
+    gas.charge(gas::call_extra_cost(transfers_value, creates_account))?;
+
+    let gas_limit = gas::call_gas_limit(spec, gas.remaining(), requested_gas);
+    gas.charge(gas_limit)?;
+    let stipend = if transfers_value {
+        gas::CALL_STIPEND
+    } else {
+        0
+    };
+
+    let (caller, address, call_value) = match kind {
+        CallKind::Call | CallKind::StaticCall => (context.address, target, value),
+        CallKind::CallCode => (context.address, context.address, value),
+        CallKind::DelegateCall => (context.caller, context.address, context.value),
+    };
+
+    Ok(CallInputs {
+        kind,
+        code_address: target,
+        context: CallContext {
+            caller,
+            origin: context.origin,
+            address,
+            gas_price: context.gas_price,
+            value: call_value,
+            data,
+            gas_limit: gas_limit + stipend,
+            access_list: Vec::new(),
+        },
+        transfer_value: value,
+        is_static: kind == CallKind::StaticCall,
+        return_offset,
+        return_size,
+    })
+}
+
+/// Shared by CREATE and CREATE2: pops the arguments, charges for the
+/// initcode and forwards all but one 64th of the remaining gas.
+pub fn handle_create(
+    spec: SpecId,
+    opcode: u8,
+    context: &CallContext,
+    memory: &mut Memory,
+    stack: &mut Stack,
+    gas: &mut Gas,
+    is_static: bool,
+) -> Result<CreateInputs> {
+    let value = stack.pop()?;
+    let offset = stack.pop()?;
+    let size = as_memory_size(stack.pop()?)?;
+    let scheme = if opcode == opcodes::CREATE2 {
+        CreateScheme::Create2 { salt: stack.pop()? }
+    } else {
+        CreateScheme::Create
+    };
+
+    if is_static {
+        return Err(EvmError::StateChangeInStaticContext);
+    }
+    if spec >= SpecId::Shanghai && size > MAX_INITCODE_SIZE {
+        return Err(EvmError::InitcodeSizeExceeded);
+    }
+
+    gas.charge(memory.expansion_cost(offset, size)?)?;
+    let init_code = memory.read_bytes(offset, size)?;
+    gas.charge(gas::create_cost(spec, size, opcode == opcodes::CREATE2))?;
+
+    let gas_limit = gas::create_gas_limit(spec, gas.remaining());
+    gas.charge(gas_limit)?;
+
+    Ok(CreateInputs {
+        scheme,
+        caller: context.address,
+        value,
+        init_code,
+        gas_limit,
+    })
+}
+
+/// SELFDESTRUCT: sends the whole balance of `address` to the beneficiary
+/// and schedules the account for deletion when the transaction ends. Since
+/// Cancun (EIP-6780) only a contract created in the same transaction is
+/// deleted; any other just hands over its balance. A deleted account
+/// burns whatever it sends to itself.
+pub fn handle_selfdestruct(
+    spec: SpecId,
+    state: &mut WorldState,
+    address: Address,
+    stack: &mut Stack,
+    gas: &mut Gas,
+) -> Result<()> {
+    let beneficiary = u256_to_address(stack.pop()?);
+    let balance = state.balance(&address);
+
+    let is_cold = state.warm_address(beneficiary);
+    let creates_account = if spec >= SpecId::SpuriousDragon {
+        !balance.is_zero() && state.account(&beneficiary).is_none_or(Account::is_empty)
+    } else {
+        !state.exists(&beneficiary)
+    };
+    gas.charge(gas::selfdestruct_cost(spec, is_cold, creates_account))?;
+
+    let destroys = spec < SpecId::Cancun || state.is_created(&address);
+    if destroys && state.mark_destroyed(address) {
+        state.record_refund(gas::selfdestruct_refund(spec));
+    }
+    if beneficiary != address && !balance.is_zero() {
+        let beneficiary_balance = state.balance(&beneficiary);
+        state.set_balance(beneficiary, beneficiary_balance + balance);
+        state.set_balance(address, U256::zero());
+    } else if destroys {
+        state.set_balance(address, U256::zero());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::address_to_u256;
+
+    #[test]
+    fn test_return_data() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+        memory.write_bytes(U256::zero(), &[1, 2, 3]).unwrap();
+
+        stack.push(U256::from(2)).unwrap();
+        stack.push(U256::one()).unwrap();
+        let output = handle_return(&mut memory, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(output, vec![2, 3]);
+        assert_eq!(gas.used(), 0);
+    }
+
+    fn push_call_args(stack: &mut Stack, gas: u64, target: Address, value: Option<U256>) {
+        // return size/offset, args size/offset
+        stack.push(U256::from(32)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::from(4)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        if let Some(value) = value {
+            stack.push(value).unwrap();
+        }
+        stack.push(address_to_u256(target)).unwrap();
+        stack.push(U256::from(gas)).unwrap();
+    }
+
+    #[test]
+    fn test_call_with_value() {
+        let caller_context = CallContext {
+            address: Address::repeat_byte(0x01),
+            ..Default::default()
+        };
+        let target = Address::repeat_byte(0x02);
+        let mut state = WorldState::new();
+        state.set_code(target, vec![0x00]);
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(100_000);
+
+        push_call_args(&mut stack, 1000, target, Some(U256::from(5)));
+        let inputs = handle_call(
+            SpecId::default(),
+            CallKind::Call,
+            &caller_context,
+            &mut state,
+            &mut memory,
+            &mut stack,
+            &mut gas,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(inputs.context.caller, Address::repeat_byte(0x01));
+        assert_eq!(inputs.context.address, target);
+        assert_eq!(inputs.context.data, vec![0; 4]);
+        assert_eq!(inputs.context.gas_limit, 1000 + gas::CALL_STIPEND);
+        assert_eq!(inputs.transfer_value, U256::from(5));
+        // One word of memory, the cold target, the value transfer and the
+        // forwarded gas.
+        let cold = gas::COLD_ACCOUNT_ACCESS - gas::WARM_STORAGE_READ;
+        assert_eq!(gas.used(), 3 + cold + gas::CALL_VALUE + 1000);
+        assert_eq!(memory.size(), 32);
+    }
+
+    #[test]
+    fn test_call_forwards_all_but_one_64th() {
+        let mut state = WorldState::new();
+        state.warm_address(Address::zero());
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(6403);
+
+        push_call_args(&mut stack, u64::MAX, Address::zero(), None);
+        let inputs = handle_call(
+            SpecId::default(),
+            CallKind::StaticCall,
+            &CallContext::default(),
+            &mut state,
+            &mut memory,
+            &mut stack,
+            &mut gas,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(inputs.context.gas_limit, 6300);
+        assert!(inputs.is_static);
+        assert_eq!(gas.remaining(), 100);
+    }
+
+    #[test]
+    fn test_delegatecall_keeps_context() {
+        let caller_context = CallContext {
+            caller: Address::repeat_byte(0xaa),
+            address: Address::repeat_byte(0xbb),
+            value: U256::from(9),
+            ..Default::default()
+        };
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(100_000);
+
+        push_call_args(&mut stack, 1000, Address::repeat_byte(0xcc), None);
+        let inputs = handle_call(
+            SpecId::default(),
+            CallKind::DelegateCall,
+            &caller_context,
+            &mut WorldState::new(),
+            &mut memory,
+            &mut stack,
+            &mut gas,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(inputs.code_address, Address::repeat_byte(0xcc));
+        assert_eq!(inputs.context.caller, Address::repeat_byte(0xaa));
+        assert_eq!(inputs.context.address, Address::repeat_byte(0xbb));
+        assert_eq!(inputs.context.value, U256::from(9));
+        assert!(inputs.transfer_value.is_zero());
+    }
+
+    #[test]
+    fn test_create2_charges_for_hashing() {
+        let mut memory = Memory::new();
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(6400 + 2 + 6);
+        memory.write_bytes(U256::zero(), &[0xaa; 32]).unwrap();
+
+        // salt, size, offset, value
+        stack.push(U256::from(7)).unwrap();
+        stack.push(U256::from(32)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::zero()).unwrap();
+        let inputs = handle_create(
+            SpecId::default(),
+            opcodes::CREATE2,
+            &CallContext::default(),
+            &mut memory,
+            &mut stack,
+            &mut gas,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            inputs.scheme,
+            CreateScheme::Create2 {
+                salt: U256::from(7)
+            }
+        );
+        assert_eq!(inputs.init_code, vec![0xaa; 32]);
+        // One word of initcode, and one more to hash it.
+        assert_eq!(inputs.gas_limit, 6300);
+        assert_eq!(gas.remaining(), 100);
+    }
+
+    #[test]
+    fn test_create_initcode_too_large() {
+        let mut stack = Stack::new();
+        stack.push(U256::from(MAX_INITCODE_SIZE + 1)).unwrap();
+        stack.push(U256::zero()).unwrap();
+        stack.push(U256::zero()).unwrap();
+        assert_eq!(
+            handle_create(
+                SpecId::default(),
+                opcodes::CREATE,
+                &CallContext::default(),
+                &mut Memory::new(),
+                &mut stack,
+                &mut Gas::new(u64::MAX),
+                false,
+            ),
+            Err(EvmError::InitcodeSizeExceeded)
+        );
+    }
+
+    #[test]
+    fn test_selfdestruct() {
+        let contract = Address::repeat_byte(0x01);
+        let beneficiary = Address::repeat_byte(0x02);
+        let mut state = WorldState::new();
+        state.set_balance(contract, U256::from(10));
+        let mut stack = Stack::new();
+        let mut gas = Gas::new(u64::MAX);
+
+        stack.push(address_to_u256(beneficiary)).unwrap();
+        handle_selfdestruct(SpecId::Shanghai, &mut state, contract, &mut stack, &mut gas).unwrap();
+
+        assert_eq!(state.balance(&contract), U256::zero());
+        assert_eq!(state.balance(&beneficiary), U256::from(10));
+        assert!(state.is_destroyed(&contract));
+        assert_eq!(state.refund(), 0);
+        // A cold beneficiary that the balance brings into existence.
+        assert_eq!(gas.used(), gas::COLD_ACCOUNT_ACCESS + gas::NEW_ACCOUNT);
+    }
+
+    #[test]
+    fn test_selfdestruct_after_cancun() {
+        let contract = Address::repeat_byte(0x01);
+        let mut state = WorldState::new();
+        state.set_balance(contract, U256::from(10));
+        let mut stack = Stack::new();
+
+        // Sending the balance to itself keeps it, and the account.
+        stack.push(address_to_u256(contract)).unwrap();
+        handle_selfdestruct(
+            SpecId::Cancun,
+            &mut state,
+            contract,
+            &mut stack,
+            &mut Gas::new(u64::MAX),
+        )
+        .unwrap();
+        assert_eq!(state.balance(&contract), U256::from(10));
+        assert!(!state.is_destroyed(&contract));
+
+        // Unless it was created in the same transaction.
+        state.mark_created(contract);
+        stack.push(address_to_u256(contract)).unwrap();
+        handle_selfdestruct(
+            SpecId::Cancun,
+            &mut state,
+            contract,
+            &mut stack,
+            &mut Gas::new(u64::MAX),
+        )
+        .unwrap();
+        assert_eq!(state.balance(&contract), U256::zero());
+        assert!(state.is_destroyed(&contract));
+    }
+
+    #[test]
+    fn test_call_value_in_static_context() {
+        let mut stack = Stack::new();
+        push_call_args(&mut stack, 1000, Address::zero(), Some(U256::one()));
+        assert_eq!(
+            handle_call(
+                SpecId::default(),
+                CallKind::Call,
+                &CallContext::default(),
+                &mut WorldState::new(),
+                &mut Memory::new(),
+                &mut stack,
+                &mut Gas::new(100_000),
+                true,
+            ),
+            Err(EvmError::StateChangeInStaticContext)
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::context::Address;
+use crate::state::Account;
+use crate::storage::TransientStorage;
+use primitive_types::U256;
+use std::collections::{HashMap, HashSet};
+
+/// A single change to the world state, holding what is needed to undo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalEntry {
+    /// An account that did not exist was created.
+    AccountCreated {
+        address: Address,
+    },
+    /// An existing account was overwritten as a whole.
+    AccountReplaced {
+        address: Address,
+        previous: Account,
+    },
+    BalanceChanged {
+        address: Address,
+        previous: U256,
+    },
+    NonceChanged {
+        address: Address,
+        previous: u64,
+    },
+    CodeChanged {
+        address: Address,
+        previous: Vec<u8>,
+    },
+    StorageChanged {
+        address: Address,
+        key: U256,
+        previous: U256,
+    },
+    TransientStorageChanged {
+        address: Address,
+        key: U256,
+        previous: U256,
+    },
+    /// An account was accessed for the first time in the transaction.
+    AddressWarmed {
+        address: Address,
+    },
+    /// A storage slot was accessed for the first time in the transaction.
+    SlotWarmed {
+        address: Address,
+        key: U256,
+    },
+    RefundChanged {
+        previous: i64,
+    },
+    /// A contract was deployed, which lets it self-destruct for good later
+    /// in the same transaction (EIP-6780).
+    ContractCreated {
+        address: Address,
+    },
+    /// An account self-destructed and is deleted when the transaction ends.
+    AccountDestroyed {
+        address: Address,
+    },
+    /// A self-destructed account was deleted at the end of the transaction.
+    AccountDeleted {
+        address: Address,
+        previous: Account,
+    },
+    /// A transaction ended, dropping what only lasts for one transaction.
+    TransactionEnded(Box<EndedTransaction>),
+}
+
+/// What [`JournalEntry::TransactionEnded`] needs to reopen the transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndedTransaction {
+    pub created: HashSet<Address>,
+    pub destroyed: HashSet<Address>,
+    pub transient: TransientStorage,
+    pub accessed_addresses: HashSet<Address>,
+    pub accessed_slots: HashSet<(Address, U256)>,
+    pub refund: i64,
+    /// Original storage values of every account that had any.
+    pub originals: Vec<(Address, HashMap<U256, U256>)>,
+}
+
+/// A point in the journal that the state can be rolled back to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub(crate) journal_len: usize,
+    pub(crate) logs_len: usize,
+    pub(crate) commits: u64,
+}
@@ -1,12 +1,30 @@
+pub mod analysis;
+pub mod context;
+pub mod disasm;
 pub mod error;
 pub mod evm;
+pub mod frame;
+pub mod gas;
 pub mod handlers;
+pub mod journal;
+pub mod log;
 pub mod memory;
 pub mod opcodes;
+pub mod precompiles;
+pub mod result;
+pub mod spec;
 pub mod stack;
+pub mod state;
 pub mod storage;
 pub mod utils;
 
+pub use context::{AccessListItem, Address, BlockEnv, CallContext};
 pub use error::EvmError;
 pub use evm::Evm;
+pub use journal::Checkpoint;
+pub use log::Log;
+pub use precompiles::{Precompile, PrecompileOutput, Precompiles};
 pub use primitive_types::U256;
+pub use result::ExecutionResult;
+pub use spec::SpecId;
+pub use state::{Account, WorldState};
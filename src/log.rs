@@ -0,0 +1,11 @@
+use crate::context::Address;
+use primitive_types::H256;
+
+/// An event emitted by one of the LOG0..LOG4 opcodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Log {
+    /// Contract that emitted the event.
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
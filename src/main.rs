@@ -7,7 +7,8 @@ fn main() {
     // Example: PUSH1 0x05, PUSH1 0x03, ADD (5 + 3 = 8)
     let bytecode = hex_to_bytes("0x6005600301").expect("Invalid bytecode");
 
-    evm.execute(&bytecode).expect("Execution failed");
+    let result = evm.execute(&bytecode);
+    assert!(result.is_success(), "Execution failed: {result:?}");
 
     match evm.stack_top() {
         Ok(result) => println!("Stack top: {}", result),
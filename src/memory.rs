@@ -1,5 +1,12 @@
+use crate::error::{EvmError, Result};
+use crate::gas;
 use primitive_types::U256;
 
+/// Largest memory size we are willing to address. Expanding this far would
+/// cost far more gas than any block can hold, so anything beyond it is
+/// treated as out of bounds rather than risking overflow.
+pub const MAX_MEMORY_SIZE: usize = u32::MAX as usize;
+
 #[derive(Debug, Default)]
 pub struct Memory {
     data: Vec<u8>,
@@ -10,37 +17,83 @@ pub fn new() -> Self {
         Self { data: Vec::new() }
     }
 
-    fn expand_to(&mut self, offset: usize) {
-        if offset >= self.data.len() {
-            let new_size = ((offset + 32) / 32) * 32;
+    /// Grows memory so that `end` bytes are addressable, rounding up to a
+    /// whole number of 32-byte words.
+    fn expand_to(&mut self, end: usize) {
+        if end > self.data.len() {
+            let new_size = end.div_ceil(32) * 32;
             self.data.resize(new_size, 0);
         }
     }
 
-    pub fn write(&mut self, offset: U256, value: u8) {
-        let offset = offset.as_usize();
-        self.expand_to(offset);
-        self.data[offset] = value;
+    /// Gas needed to make `[offset, offset + size)` addressable, given the
+    /// current size. Zero-sized accesses never expand memory.
+    pub fn expansion_cost(&self, offset: U256, size: usize) -> Result<u64> {
+        if size == 0 {
+            return Ok(0);
+        }
+        let end = checked_end(offset, size)?;
+        if end <= self.data.len() {
+            return Ok(0);
+        }
+        let current_words = self.data.len().div_ceil(32) as u64;
+        let new_words = end.div_ceil(32) as u64;
+        Ok(gas::memory_cost(new_words) - gas::memory_cost(current_words))
     }
 
-    pub fn read(&mut self, offset: U256) -> u8 {
-        let offset = offset.as_usize();
-        if offset >= self.data.len() {
-            self.expand_to(offset);
+    /// Makes `[offset, offset + size)` addressable without touching it.
+    pub fn expand(&mut self, offset: U256, size: usize) -> Result<()> {
+        if size == 0 {
+            return Ok(());
         }
-        self.data[offset]
+        let end = checked_end(offset, size)?;
+        self.expand_to(end);
+        Ok(())
+    }
+
+    pub fn write(&mut self, offset: U256, value: u8) -> Result<()> {
+        let end = checked_end(offset, 1)?;
+        self.expand_to(end);
+        self.data[end - 1] = value;
+        Ok(())
     }
 
-    pub fn write_bytes(&mut self, offset: U256, data: &[u8]) {
-        let offset = offset.as_usize();
-        self.expand_to(offset + data.len());
-        self.data[offset..offset + data.len()].copy_from_slice(data);
+    pub fn read(&mut self, offset: U256) -> Result<u8> {
+        let end = checked_end(offset, 1)?;
+        self.expand_to(end);
+        Ok(self.data[end - 1])
     }
 
-    pub fn read_bytes(&mut self, offset: U256, size: usize) -> Vec<u8> {
-        let offset = offset.as_usize();
-        self.expand_to(offset + size);
-        self.data[offset..offset + size].to_vec()
+    pub fn write_bytes(&mut self, offset: U256, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let end = checked_end(offset, data.len())?;
+        self.expand_to(end);
+        self.data[end - data.len()..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    pub fn read_bytes(&mut self, offset: U256, size: usize) -> Result<Vec<u8>> {
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        let end = checked_end(offset, size)?;
+        self.expand_to(end);
+        Ok(self.data[end - size..end].to_vec())
+    }
+
+    /// Copies `size` bytes from `src` to `dst`; the regions may overlap.
+    pub fn copy(&mut self, dst: U256, src: U256, size: usize) -> Result<()> {
+        if size == 0 {
+            return Ok(());
+        }
+        let dst_end = checked_end(dst, size)?;
+        let src_end = checked_end(src, size)?;
+        self.expand_to(dst_end.max(src_end));
+        self.data
+            .copy_within(src_end - size..src_end, dst_end - size);
+        Ok(())
     }
 
     pub fn size(&self) -> usize {
@@ -48,6 +101,22 @@ pub fn size(&self) -> usize {
     }
 }
 
+/// Converts a stack value used as a memory size into a `usize`.
+pub fn as_memory_size(size: U256) -> Result<usize> {
+    if size > U256::from(MAX_MEMORY_SIZE) {
+        return Err(EvmError::MemoryOutOfBounds);
+    }
+    Ok(size.as_usize())
+}
+
+fn checked_end(offset: U256, size: usize) -> Result<usize> {
+    let offset = as_memory_size(offset)?;
+    offset
+        .checked_add(size)
+        .filter(|end| *end <= MAX_MEMORY_SIZE)
+        .ok_or(EvmError::MemoryOutOfBounds)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,14 +124,67 @@ mod tests {
     #[test]
     fn test_memory_write_read() {
         let mut memory = Memory::new();
-        memory.write(U256::from(0), 0x42);
-        assert_eq!(memory.read(U256::from(0)), 0x42);
+        memory.write(U256::from(0), 0x42).unwrap();
+        assert_eq!(memory.read(U256::from(0)).unwrap(), 0x42);
     }
 
     #[test]
     fn test_memory_expansion() {
         let mut memory = Memory::new();
-        memory.write(U256::from(100), 0xff);
+        memory.write(U256::from(100), 0xff).unwrap();
         assert!(memory.size() >= 100);
     }
+
+    #[test]
+    fn test_memory_word_aligned() {
+        let mut memory = Memory::new();
+        memory.write_bytes(U256::zero(), &[0u8; 32]).unwrap();
+        assert_eq!(memory.size(), 32);
+        memory.write(U256::from(32), 0x01).unwrap();
+        assert_eq!(memory.size(), 64);
+        memory.read_bytes(U256::from(1000), 0).unwrap();
+        assert_eq!(memory.size(), 64);
+    }
+
+    #[test]
+    fn test_memory_copy_overlapping() {
+        let mut memory = Memory::new();
+        memory.write_bytes(U256::zero(), &[1, 2, 3, 4]).unwrap();
+        memory.copy(U256::one(), U256::zero(), 3).unwrap();
+        assert_eq!(
+            memory.read_bytes(U256::zero(), 4).unwrap(),
+            vec![1, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_memory_huge_offset() {
+        let mut memory = Memory::new();
+        assert_eq!(memory.read(U256::MAX), Err(EvmError::MemoryOutOfBounds));
+        assert_eq!(
+            memory.read_bytes(U256::from(MAX_MEMORY_SIZE), 32),
+            Err(EvmError::MemoryOutOfBounds)
+        );
+        assert_eq!(
+            memory.expansion_cost(U256::MAX, 32),
+            Err(EvmError::MemoryOutOfBounds)
+        );
+        assert_eq!(memory.size(), 0);
+    }
+
+    #[test]
+    fn test_expansion_cost() {
+        let mut memory = Memory::new();
+        assert_eq!(memory.expansion_cost(U256::zero(), 32).unwrap(), 3);
+        assert_eq!(memory.expansion_cost(U256::MAX, 0).unwrap(), 0);
+        // 1024 words: 3 * 1024 + 1024^2 / 512
+        assert_eq!(
+            memory.expansion_cost(U256::zero(), 32 * 1024).unwrap(),
+            3072 + 2048
+        );
+
+        memory.write_bytes(U256::zero(), &[0u8; 32]).unwrap();
+        assert_eq!(memory.expansion_cost(U256::zero(), 32).unwrap(), 0);
+        assert_eq!(memory.expansion_cost(U256::from(1), 32).unwrap(), 3);
+    }
 }
@@ -34,9 +34,59 @@
 pub const SHR: u8 = 0x1c;
 pub const SAR: u8 = 0x1d;
 
+// Hashing
+pub const KECCAK256: u8 = 0x20;
+
+// Environmental Information
+pub const ADDRESS: u8 = 0x30;
+pub const BALANCE: u8 = 0x31;
+pub const ORIGIN: u8 = 0x32;
+pub const CALLER: u8 = 0x33;
+pub const CALLVALUE: u8 = 0x34;
+pub const CALLDATALOAD: u8 = 0x35;
+pub const CALLDATASIZE: u8 = 0x36;
+pub const CALLDATACOPY: u8 = 0x37;
+pub const CODESIZE: u8 = 0x38;
+pub const CODECOPY: u8 = 0x39;
+pub const GASPRICE: u8 = 0x3a;
+pub const EXTCODESIZE: u8 = 0x3b;
+pub const EXTCODECOPY: u8 = 0x3c;
+pub const RETURNDATASIZE: u8 = 0x3d;
+pub const RETURNDATACOPY: u8 = 0x3e;
+pub const EXTCODEHASH: u8 = 0x3f;
+
+// Block Information
+pub const BLOCKHASH: u8 = 0x40;
+pub const COINBASE: u8 = 0x41;
+pub const TIMESTAMP: u8 = 0x42;
+pub const NUMBER: u8 = 0x43;
+pub const PREVRANDAO: u8 = 0x44;
+pub const GASLIMIT: u8 = 0x45;
+pub const CHAINID: u8 = 0x46;
+pub const SELFBALANCE: u8 = 0x47;
+pub const BASEFEE: u8 = 0x48;
+pub const BLOBHASH: u8 = 0x49;
+pub const BLOBBASEFEE: u8 = 0x4a;
+
+// Memory Operations
+pub const MLOAD: u8 = 0x51;
+pub const MSTORE: u8 = 0x52;
+pub const MSTORE8: u8 = 0x53;
+pub const MSIZE: u8 = 0x59;
+pub const MCOPY: u8 = 0x5e;
+
 // Storage Operations
 pub const SLOAD: u8 = 0x54;
 pub const SSTORE: u8 = 0x55;
+pub const TLOAD: u8 = 0x5c;
+pub const TSTORE: u8 = 0x5d;
+
+// Control Flow Operations
+pub const JUMP: u8 = 0x56;
+pub const JUMPI: u8 = 0x57;
+pub const PC: u8 = 0x58;
+pub const GAS: u8 = 0x5a;
+pub const JUMPDEST: u8 = 0x5b;
 
 // Stack Manipulation
 pub const POP: u8 = 0x50;
@@ -108,6 +158,89 @@
 pub const SWAP15: u8 = 0x9e;
 pub const SWAP16: u8 = 0x9f;
 
+// Logging Operations
+pub const LOG0: u8 = 0xa0;
+pub const LOG1: u8 = 0xa1;
+pub const LOG2: u8 = 0xa2;
+pub const LOG3: u8 = 0xa3;
+pub const LOG4: u8 = 0xa4;
+
+// System Operations
+pub const CREATE: u8 = 0xf0;
+pub const CALL: u8 = 0xf1;
+pub const CALLCODE: u8 = 0xf2;
+pub const RETURN: u8 = 0xf3;
+pub const DELEGATECALL: u8 = 0xf4;
+pub const CREATE2: u8 = 0xf5;
+pub const STATICCALL: u8 = 0xfa;
+pub const REVERT: u8 = 0xfd;
+/// The designated invalid instruction, which always halts (EIP-141).
+pub const INVALID: u8 = 0xfe;
+pub const SELFDESTRUCT: u8 = 0xff;
+
+/// Name and immediate size of an assigned opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    pub mnemonic: &'static str,
+    /// Bytes of data embedded in the code after the opcode; only the PUSH
+    /// family has any.
+    pub immediate_size: usize,
+}
+
+/// Builds the opcode table from the constants above, naming each entry
+/// after its constant.
+macro_rules! opcode_table {
+    ($($name:ident),* $(,)?) => {{
+        let mut table: [Option<OpcodeInfo>; 256] = [None; 256];
+        $(
+            table[$name as usize] = Some(OpcodeInfo {
+                mnemonic: stringify!($name),
+                immediate_size: 0,
+            });
+        )*
+        table
+    }};
+}
+
+const OPCODE_INFO: [Option<OpcodeInfo>; 256] = {
+    let mut table = opcode_table! {
+        STOP, ADD, MUL, SUB, DIV, SDIV, MOD, SMOD, ADDMOD, MULMOD, EXP, SIGNEXTEND,
+        LT, GT, SLT, SGT, EQ, ISZERO, AND, OR, XOR, NOT, BYTE, SHL, SHR, SAR, KECCAK256,
+        ADDRESS, BALANCE, ORIGIN, CALLER, CALLVALUE, CALLDATALOAD, CALLDATASIZE, CALLDATACOPY,
+        CODESIZE, CODECOPY, GASPRICE, EXTCODESIZE, EXTCODECOPY, RETURNDATASIZE, RETURNDATACOPY,
+        EXTCODEHASH, BLOCKHASH, COINBASE, TIMESTAMP, NUMBER, PREVRANDAO, GASLIMIT, CHAINID,
+        SELFBALANCE, BASEFEE, BLOBHASH, BLOBBASEFEE,
+        POP, MLOAD, MSTORE, MSTORE8, SLOAD, SSTORE, JUMP, JUMPI, PC, MSIZE, GAS, JUMPDEST,
+        TLOAD, TSTORE, MCOPY,
+        PUSH0, PUSH1, PUSH2, PUSH3, PUSH4, PUSH5, PUSH6, PUSH7, PUSH8, PUSH9, PUSH10, PUSH11,
+        PUSH12, PUSH13, PUSH14, PUSH15, PUSH16, PUSH17, PUSH18, PUSH19, PUSH20, PUSH21, PUSH22,
+        PUSH23, PUSH24, PUSH25, PUSH26, PUSH27, PUSH28, PUSH29, PUSH30, PUSH31, PUSH32,
+        DUP1, DUP2, DUP3, DUP4, DUP5, DUP6, DUP7, DUP8, DUP9, DUP10, DUP11, DUP12, DUP13, DUP14,
+        DUP15, DUP16,
+        SWAP1, SWAP2, SWAP3, SWAP4, SWAP5, SWAP6, SWAP7, SWAP8, SWAP9, SWAP10, SWAP11, SWAP12,
+        SWAP13, SWAP14, SWAP15, SWAP16,
+        LOG0, LOG1, LOG2, LOG3, LOG4,
+        CREATE, CALL, CALLCODE, RETURN, DELEGATECALL, CREATE2, STATICCALL, REVERT, INVALID,
+        SELFDESTRUCT,
+    };
+
+    let mut op = PUSH1;
+    while op <= PUSH32 {
+        if let Some(info) = &mut table[op as usize] {
+            info.immediate_size = (op - PUSH0) as usize;
+        }
+        op += 1;
+    }
+
+    table
+};
+
+/// Name and immediate size of `opcode`, or `None` for a byte that no fork
+/// assigns.
+pub fn info(opcode: u8) -> Option<OpcodeInfo> {
+    OPCODE_INFO[opcode as usize]
+}
+
 #[inline]
 pub fn is_push(opcode: u8) -> bool {
     (PUSH0..=PUSH32).contains(&opcode)
@@ -122,3 +255,32 @@ pub fn is_dup(opcode: u8) -> bool {
 pub fn is_swap(opcode: u8) -> bool {
     (SWAP1..=SWAP16).contains(&opcode)
 }
+
+#[inline]
+pub fn is_log(opcode: u8) -> bool {
+    (LOG0..=LOG4).contains(&opcode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opcode_info() {
+        assert_eq!(
+            info(ADD),
+            Some(OpcodeInfo {
+                mnemonic: "ADD",
+                immediate_size: 0
+            })
+        );
+        assert_eq!(info(PUSH0).unwrap().immediate_size, 0);
+        assert_eq!(info(PUSH1).unwrap().immediate_size, 1);
+        assert_eq!(info(PUSH32).unwrap().mnemonic, "PUSH32");
+        assert_eq!(info(PUSH32).unwrap().immediate_size, 32);
+        assert_eq!(info(SWAP16).unwrap().mnemonic, "SWAP16");
+        assert_eq!(info(INVALID).unwrap().mnemonic, "INVALID");
+        assert_eq!(info(0x0c), None);
+        assert_eq!(info(0xef), None);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas};
+use crate::error::EvmError;
+
+/// `rounds ++ h ++ m ++ t ++ f`
+const INPUT_SIZE: usize = 213;
+const ROUND: u64 = 1;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Order in which each round reads the message words; rounds past the
+/// tenth start over.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// 0x09: the BLAKE2b compression function F (EIP-152) with a caller-chosen
+/// number of rounds, each costing one gas. The input must be exactly 213
+/// bytes with a final-block flag of 0 or 1.
+pub fn blake2f(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if input.len() != INPUT_SIZE {
+        return Err(EvmError::PrecompileFailure(
+            "BLAKE2f input must be 213 bytes".to_string(),
+        ));
+    }
+    let rounds = u32::from_be_bytes(input[..4].try_into().expect("slice is 4 bytes"));
+    let cost = ROUND * u64::from(rounds);
+    check_gas(cost, gas_limit)?;
+
+    let last_block = match input[212] {
+        0 => false,
+        1 => true,
+        _ => {
+            return Err(EvmError::PrecompileFailure(
+                "BLAKE2f final block flag must be 0 or 1".to_string(),
+            ));
+        }
+    };
+    let words = |bytes: &[u8]| -> Vec<u64> {
+        bytes
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().expect("chunk is 8 bytes")))
+            .collect()
+    };
+    let mut h: [u64; 8] = words(&input[4..68]).try_into().expect("8 words");
+    let m: [u64; 16] = words(&input[68..196]).try_into().expect("16 words");
+    let t: [u64; 2] = words(&input[196..212]).try_into().expect("2 words");
+
+    compress(rounds, &mut h, &m, t, last_block);
+
+    let output = h.iter().flat_map(|word| word.to_le_bytes()).collect();
+    Ok(PrecompileOutput::new(cost, output))
+}
+
+fn compress(rounds: u32, h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], last_block: bool) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if last_block {
+        v[14] = !v[14];
+    }
+
+    for round in 0..rounds as usize {
+        let s = &SIGMA[round % 10];
+        mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// The G mixing function, folding two message words into four lanes.
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{bytes_to_hex, hex_to_bytes};
+
+    /// Test vector 5 of EIP-152: 12 rounds over "abc", i.e. BLAKE2b-512.
+    const INPUT: &str = "0x0000000c\
+        48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+        d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b\
+        6162630000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        03000000000000000000000000000000\
+        01";
+
+    #[test]
+    fn test_blake2f() {
+        let output = blake2f(&hex_to_bytes(INPUT).unwrap(), 12).unwrap();
+        assert_eq!(output.gas_used, 12);
+        assert_eq!(
+            bytes_to_hex(&output.output),
+            "0xba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(
+            blake2f(&hex_to_bytes(INPUT).unwrap(), 11),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_blake2f_rejects_malformed_input() {
+        let input = hex_to_bytes(INPUT).unwrap();
+        assert!(matches!(
+            blake2f(&input[..212], 12),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+
+        let mut input = input;
+        input[212] = 2;
+        assert!(matches!(
+            blake2f(&input, 12),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+    }
+}
@@ -0,0 +1,538 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas};
+use crate::error::{EvmError, Result};
+use bls12_381::hash_to_curve::{HashToField, MapToCurve};
+use bls12_381::{
+    G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar, multi_miller_loop,
+};
+
+// Prices from EIP-2537.
+const G1_ADD: u64 = 375;
+const G1_MUL: u64 = 12_000;
+const G2_ADD: u64 = 600;
+const G2_MUL: u64 = 22_500;
+const PAIRING: u64 = 37_700;
+const PAIRING_PAIR: u64 = 32_600;
+const MAP_FP_TO_G1: u64 = 5_500;
+const MAP_FP2_TO_G2: u64 = 23_800;
+
+/// Every base field element is padded from 48 to 64 bytes.
+const FP_SIZE: usize = 64;
+const G1_SIZE: usize = 2 * FP_SIZE;
+const G2_SIZE: usize = 4 * FP_SIZE;
+const SCALAR_SIZE: usize = 32;
+
+/// Modulus of the base field, big-endian.
+const MODULUS: [u8; 48] = [
+    0x1a, 0x01, 0x11, 0xea, 0x39, 0x7f, 0xe6, 0x9a, 0x4b, 0x1b, 0xa7, 0xb6, 0x43, 0x4b, 0xac, 0xd7,
+    0x64, 0x77, 0x4b, 0x84, 0xf3, 0x85, 0x12, 0xbf, 0x67, 0x30, 0xd2, 0xa0, 0xf6, 0xb0, 0xf6, 0x24,
+    0x1e, 0xab, 0xff, 0xfe, 0xb1, 0x53, 0xff, 0xff, 0xb9, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xaa, 0xab,
+];
+
+/// Per-mille discount on an MSM of `k` points, indexed by `k - 1`; longer
+/// lists get the last entry.
+const G1_MSM_DISCOUNT: [u64; 128] = [
+    1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682, 677, 673, 669, 665,
+    661, 658, 654, 651, 648, 645, 642, 640, 637, 635, 632, 630, 627, 625, 623, 621, 619, 617, 615,
+    613, 611, 609, 608, 606, 604, 603, 601, 599, 598, 596, 595, 593, 592, 591, 589, 588, 586, 585,
+    584, 582, 581, 580, 579, 577, 576, 575, 574, 573, 572, 570, 569, 568, 567, 566, 565, 564, 563,
+    562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 551, 550, 549, 548, 547, 547, 546, 545,
+    544, 543, 542, 541, 540, 540, 539, 538, 537, 536, 536, 535, 534, 533, 532, 532, 531, 530, 529,
+    528, 528, 527, 526, 525, 525, 524, 523, 522, 522, 521, 520, 520, 519,
+];
+const G2_MSM_DISCOUNT: [u64; 128] = [
+    1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732, 724, 717, 711, 704,
+    699, 693, 688, 683, 679, 674, 670, 666, 663, 659, 655, 652, 649, 646, 643, 640, 637, 634, 632,
+    629, 627, 624, 622, 620, 618, 615, 613, 611, 609, 607, 606, 604, 602, 600, 598, 597, 595, 593,
+    592, 590, 589, 587, 586, 584, 583, 582, 580, 579, 578, 576, 575, 574, 573, 571, 570, 569, 568,
+    567, 566, 565, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 552, 551, 550, 549,
+    548, 547, 546, 545, 545, 544, 543, 542, 541, 541, 540, 539, 538, 537, 537, 536, 535, 535, 534,
+    533, 532, 532, 531, 530, 530, 529, 528, 528, 527, 526, 526, 525, 524, 524,
+];
+
+type Fp = <G1Projective as MapToCurve>::Field;
+type Fp2 = <G2Projective as MapToCurve>::Field;
+
+/// 0x0b: the sum of two G1 points. The points need only lie on the curve.
+pub fn bls12_g1_add(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    check_gas(G1_ADD, gas_limit)?;
+    if input.len() != 2 * G1_SIZE {
+        return Err(failure("G1 addition input must be 256 bytes"));
+    }
+    let a = read_g1(&input[..G1_SIZE], false)?;
+    let b = read_g1(&input[G1_SIZE..], false)?;
+    let sum = G1Affine::from(G1Projective::from(a) + b);
+    Ok(PrecompileOutput::new(G1_ADD, encode_g1(&sum)))
+}
+
+/// 0x0c: `sum(s_i * P_i)` over a non-empty list of `P ++ s` entries, priced
+/// at a discount that grows with the list.
+pub fn bls12_g1_msm(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    const ENTRY_SIZE: usize = G1_SIZE + SCALAR_SIZE;
+    let k = input.len() / ENTRY_SIZE;
+    let cost = msm_cost(k, G1_MUL, &G1_MSM_DISCOUNT);
+    check_gas(cost, gas_limit)?;
+    if k == 0 || !input.len().is_multiple_of(ENTRY_SIZE) {
+        return Err(failure("G1 MSM input is not a list of point-scalar pairs"));
+    }
+
+    let mut sum = G1Projective::identity();
+    for entry in input.chunks_exact(ENTRY_SIZE) {
+        let point = read_g1(&entry[..G1_SIZE], true)?;
+        sum += point * read_scalar(&entry[G1_SIZE..]);
+    }
+    Ok(PrecompileOutput::new(cost, encode_g1(&sum.into())))
+}
+
+/// 0x0d: the sum of two G2 points. The points need only lie on the curve.
+pub fn bls12_g2_add(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    check_gas(G2_ADD, gas_limit)?;
+    if input.len() != 2 * G2_SIZE {
+        return Err(failure("G2 addition input must be 512 bytes"));
+    }
+    let a = read_g2(&input[..G2_SIZE], false)?;
+    let b = read_g2(&input[G2_SIZE..], false)?;
+    let sum = G2Affine::from(G2Projective::from(a) + b);
+    Ok(PrecompileOutput::new(G2_ADD, encode_g2(&sum)))
+}
+
+/// 0x0e: the G2 counterpart of [`bls12_g1_msm`].
+pub fn bls12_g2_msm(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    const ENTRY_SIZE: usize = G2_SIZE + SCALAR_SIZE;
+    let k = input.len() / ENTRY_SIZE;
+    let cost = msm_cost(k, G2_MUL, &G2_MSM_DISCOUNT);
+    check_gas(cost, gas_limit)?;
+    if k == 0 || !input.len().is_multiple_of(ENTRY_SIZE) {
+        return Err(failure("G2 MSM input is not a list of point-scalar pairs"));
+    }
+
+    let mut sum = G2Projective::identity();
+    for entry in input.chunks_exact(ENTRY_SIZE) {
+        let point = read_g2(&entry[..G2_SIZE], true)?;
+        sum += point * read_scalar(&entry[G2_SIZE..]);
+    }
+    Ok(PrecompileOutput::new(cost, encode_g2(&sum.into())))
+}
+
+/// 0x0f: whether the product of the pairings of a non-empty list of
+/// (G1, G2) pairs is one, returned as a 32-byte word.
+pub fn bls12_pairing(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    const PAIR_SIZE: usize = G1_SIZE + G2_SIZE;
+    let k = input.len() / PAIR_SIZE;
+    let cost = PAIRING + PAIRING_PAIR * k as u64;
+    check_gas(cost, gas_limit)?;
+    if k == 0 || !input.len().is_multiple_of(PAIR_SIZE) {
+        return Err(failure("pairing input is not a list of pairs"));
+    }
+
+    let mut pairs = Vec::with_capacity(k);
+    for pair in input.chunks_exact(PAIR_SIZE) {
+        let g1 = read_g1(&pair[..G1_SIZE], true)?;
+        let g2 = read_g2(&pair[G1_SIZE..], true)?;
+        // A pair with the identity contributes a factor of one.
+        if !bool::from(g1.is_identity() | g2.is_identity()) {
+            pairs.push((g1, G2Prepared::from(g2)));
+        }
+    }
+
+    let terms: Vec<_> = pairs.iter().map(|(g1, g2)| (g1, g2)).collect();
+    let success =
+        terms.is_empty() || multi_miller_loop(&terms).final_exponentiation() == Gt::identity();
+    let mut output = vec![0u8; 32];
+    output[31] = u8::from(success);
+    Ok(PrecompileOutput::new(cost, output))
+}
+
+/// 0x10: maps a base field element to G1 with the simplified SWU map and
+/// cofactor clearing of RFC 9380.
+pub fn bls12_map_fp_to_g1(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    check_gas(MAP_FP_TO_G1, gas_limit)?;
+    if input.len() != FP_SIZE {
+        return Err(failure("field element must be 64 bytes"));
+    }
+    read_fp(input)?;
+    // A canonical element reduces to itself.
+    let u = Fp::from_okm(input.into());
+    let point = G1Projective::map_to_curve(&u).clear_h();
+    Ok(PrecompileOutput::new(
+        MAP_FP_TO_G1,
+        encode_g1(&point.into()),
+    ))
+}
+
+/// 0x11: the G2 counterpart of [`bls12_map_fp_to_g1`], from `c0 ++ c1`.
+pub fn bls12_map_fp2_to_g2(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    check_gas(MAP_FP2_TO_G2, gas_limit)?;
+    if input.len() != 2 * FP_SIZE {
+        return Err(failure("quadratic extension element must be 128 bytes"));
+    }
+    read_fp(&input[..FP_SIZE])?;
+    read_fp(&input[FP_SIZE..])?;
+    let u = Fp2::from_okm(input.into());
+    let point = G2Projective::map_to_curve(&u).clear_h();
+    Ok(PrecompileOutput::new(
+        MAP_FP2_TO_G2,
+        encode_g2(&point.into()),
+    ))
+}
+
+/// `k * mul * discount(k) / 1000`.
+fn msm_cost(k: usize, mul: u64, discounts: &[u64; 128]) -> u64 {
+    let discount = match k {
+        0 => return 0,
+        k => discounts[k.min(discounts.len()) - 1],
+    };
+    k as u64 * mul * discount / 1000
+}
+
+fn failure(reason: &str) -> EvmError {
+    EvmError::PrecompileFailure(format!("BLS12-381 {reason}"))
+}
+
+/// A base field element: 16 zero bytes, then 48 bytes below the modulus.
+fn read_fp(bytes: &[u8]) -> Result<[u8; 48]> {
+    let (padding, value) = bytes.split_at(FP_SIZE - 48);
+    if padding.iter().any(|&byte| byte != 0) || value >= MODULUS.as_slice() {
+        return Err(failure("coordinate is not a field element"));
+    }
+    Ok(value.try_into().expect("slice is 48 bytes"))
+}
+
+/// A G1 point `x ++ y`, where all zeros stand for the point at infinity.
+/// MSM and pairing inputs must also lie in the prime-order subgroup.
+fn read_g1(bytes: &[u8], subgroup: bool) -> Result<G1Affine> {
+    let mut uncompressed = [0u8; 96];
+    uncompressed[..48].copy_from_slice(&read_fp(&bytes[..FP_SIZE])?);
+    uncompressed[48..].copy_from_slice(&read_fp(&bytes[FP_SIZE..])?);
+    if uncompressed.iter().all(|&byte| byte == 0) {
+        return Ok(G1Affine::identity());
+    }
+
+    let point = Option::<G1Affine>::from(G1Affine::from_uncompressed_unchecked(&uncompressed))
+        .ok_or_else(|| failure("G1 point is invalid"))?;
+    if !bool::from(point.is_on_curve()) {
+        return Err(failure("G1 point is not on the curve"));
+    }
+    if subgroup && !bool::from(point.is_torsion_free()) {
+        return Err(failure("G1 point is not in the subgroup"));
+    }
+    Ok(point)
+}
+
+/// A G2 point `x_c0 ++ x_c1 ++ y_c0 ++ y_c1`, checked like [`read_g1`].
+fn read_g2(bytes: &[u8], subgroup: bool) -> Result<G2Affine> {
+    // The library orders each coordinate `c1 ++ c0`.
+    let mut uncompressed = [0u8; 192];
+    for (i, fp) in bytes.chunks_exact(FP_SIZE).enumerate() {
+        let offset = 48 * (i ^ 1);
+        uncompressed[offset..offset + 48].copy_from_slice(&read_fp(fp)?);
+    }
+    if uncompressed.iter().all(|&byte| byte == 0) {
+        return Ok(G2Affine::identity());
+    }
+
+    let point = Option::<G2Affine>::from(G2Affine::from_uncompressed_unchecked(&uncompressed))
+        .ok_or_else(|| failure("G2 point is invalid"))?;
+    if !bool::from(point.is_on_curve()) {
+        return Err(failure("G2 point is not on the curve"));
+    }
+    if subgroup && !bool::from(point.is_torsion_free()) {
+        return Err(failure("G2 point is not in the subgroup"));
+    }
+    Ok(point)
+}
+
+/// A big-endian scalar of any size, reduced by the group order.
+fn read_scalar(bytes: &[u8]) -> Scalar {
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(bytes);
+    wide[..32].reverse();
+    Scalar::from_bytes_wide(&wide)
+}
+
+/// Pads each 48-byte coordinate of an uncompressed point to 64 bytes.
+fn pad_coordinates(coordinates: &[u8]) -> Vec<u8> {
+    coordinates
+        .chunks_exact(48)
+        .flat_map(|fp| [&[0u8; FP_SIZE - 48][..], fp].concat())
+        .collect()
+}
+
+/// Encodes a G1 point as `x ++ y`, with zeros for the point at infinity.
+fn encode_g1(point: &G1Affine) -> Vec<u8> {
+    if bool::from(point.is_identity()) {
+        return vec![0; G1_SIZE];
+    }
+    pad_coordinates(&point.to_uncompressed())
+}
+
+/// Encodes a G2 point as `x_c0 ++ x_c1 ++ y_c0 ++ y_c1`, with zeros for the
+/// point at infinity.
+fn encode_g2(point: &G2Affine) -> Vec<u8> {
+    if bool::from(point.is_identity()) {
+        return vec![0; G2_SIZE];
+    }
+    let uncompressed = point.to_uncompressed();
+    let mut reordered = [0u8; 192];
+    for (i, fp) in uncompressed.chunks_exact(48).enumerate() {
+        let offset = 48 * (i ^ 1);
+        reordered[offset..offset + 48].copy_from_slice(fp);
+    }
+    pad_coordinates(&reordered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    fn g1_generator() -> Vec<u8> {
+        encode_g1(&G1Affine::generator())
+    }
+
+    fn g2_generator() -> Vec<u8> {
+        encode_g2(&G2Affine::generator())
+    }
+
+    fn scalar(value: u64) -> Vec<u8> {
+        hex_to_bytes(&format!("{value:064x}")).unwrap()
+    }
+
+    /// Runs every case of an EIP-2537 test vector file and compares the
+    /// output byte for byte. The files predate the final gas schedule, so
+    /// their `Gas` fields are not checked.
+    fn check_vectors(json: &str, precompile: fn(&[u8], u64) -> PrecompileResult) {
+        let field = |case: &str, key: &str| -> String {
+            let start = case.find(&format!("\"{key}\": \"")).expect(key) + key.len() + 5;
+            let len = case[start..].find('"').expect("field is closed");
+            case[start..start + len].to_string()
+        };
+        let cases: Vec<_> = json
+            .split('}')
+            .filter(|case| case.contains("\"Input\""))
+            .collect();
+        assert!(!cases.is_empty());
+        for case in cases {
+            let name = field(case, "Name");
+            let input = hex_to_bytes(&field(case, "Input")).unwrap();
+            let output = precompile(&input, u64::MAX).unwrap_or_else(|err| panic!("{name}: {err}"));
+            assert_eq!(
+                hex::encode(output.output),
+                field(case, "Expected"),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generator_encoding() {
+        let g1 = g1_generator();
+        assert_eq!(
+            hex::encode(&g1[..64]),
+            "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0f\
+             c3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        );
+        assert_eq!(read_g1(&g1, true), Ok(G1Affine::generator()));
+
+        // x_c0 leads, where the library's own encoding puts x_c1 first.
+        let g2 = g2_generator();
+        assert_eq!(
+            hex::encode(&g2[..64]),
+            "00000000000000000000000000000000024aa2b2f08f0a91260805272dc51051\
+             c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+        );
+        assert_eq!(read_g2(&g2, true), Ok(G2Affine::generator()));
+    }
+
+    #[test]
+    fn test_g1_add() {
+        let g1 = g1_generator();
+        let output = bls12_g1_add(&[g1.clone(), g1.clone()].concat(), 375).unwrap();
+        assert_eq!(output.gas_used, 375);
+        let double = G1Affine::from(G1Projective::generator().double());
+        assert_eq!(output.output, encode_g1(&double));
+
+        // Adding the point at infinity changes nothing.
+        let output = bls12_g1_add(&[g1.clone(), vec![0; 128]].concat(), 375);
+        assert_eq!(output.unwrap().output, g1);
+
+        assert_eq!(
+            bls12_g1_add(&[g1.clone(), g1].concat(), 374),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_g1_add_rejects_invalid_encoding() {
+        let g1 = g1_generator();
+
+        // Nonzero padding above the 48-byte value.
+        let mut bad = [g1.clone(), g1.clone()].concat();
+        bad[0] = 1;
+        assert!(matches!(
+            bls12_g1_add(&bad, 375),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+
+        // A coordinate equal to the modulus.
+        let mut bad = [g1.clone(), g1.clone()].concat();
+        bad[16..64].copy_from_slice(&MODULUS);
+        assert!(bls12_g1_add(&bad, 375).is_err());
+
+        // (x, y + 1) is off the curve.
+        let mut bad = [g1.clone(), g1.clone()].concat();
+        bad[127] ^= 1;
+        assert!(bls12_g1_add(&bad, 375).is_err());
+
+        assert!(bls12_g1_add(&[g1.clone(), g1].concat()[..255], 375).is_err());
+    }
+
+    #[test]
+    fn test_g2_add() {
+        let g2 = g2_generator();
+        let output = bls12_g2_add(&[g2.clone(), g2.clone()].concat(), 600).unwrap();
+        assert_eq!(output.gas_used, 600);
+        let double = G2Affine::from(G2Projective::generator().double());
+        assert_eq!(output.output, encode_g2(&double));
+    }
+
+    #[test]
+    fn test_g2_add_vectors() {
+        check_vectors(include_str!("testdata/add_G2_bls.json"), bls12_g2_add);
+    }
+
+    #[test]
+    fn test_g1_msm() {
+        let g1 = g1_generator();
+        // 2G + 3G = 5G
+        let input = [g1.clone(), scalar(2), g1.clone(), scalar(3)].concat();
+        let output = bls12_g1_msm(&input, 100_000).unwrap();
+        assert_eq!(output.gas_used, 2 * 12_000 * 949 / 1000);
+        let expected = G1Affine::from(G1Projective::generator() * Scalar::from(5));
+        assert_eq!(output.output, encode_g1(&expected));
+
+        // Scalars need not be reduced: the group order times G is the identity.
+        let order =
+            hex_to_bytes("0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001")
+                .unwrap();
+        let output = bls12_g1_msm(&[g1.clone(), order].concat(), 12_000);
+        assert_eq!(output.unwrap().output, vec![0; 128]);
+
+        assert!(matches!(
+            bls12_g1_msm(&[], 100_000),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+        assert_eq!(bls12_g1_msm(&input, 22_775), Err(EvmError::OutOfGas));
+    }
+
+    #[test]
+    fn test_g2_msm() {
+        let g2 = g2_generator();
+        let output = bls12_g2_msm(&[g2, scalar(2)].concat(), 22_500).unwrap();
+        assert_eq!(output.gas_used, 22_500);
+        let double = G2Affine::from(G2Projective::generator().double());
+        assert_eq!(output.output, encode_g2(&double));
+    }
+
+    #[test]
+    fn test_g2_msm_vectors() {
+        check_vectors(include_str!("testdata/msm_G2_bls.json"), bls12_g2_msm);
+    }
+
+    #[test]
+    fn test_msm_cost() {
+        assert_eq!(msm_cost(1, G1_MUL, &G1_MSM_DISCOUNT), 12_000);
+        assert_eq!(msm_cost(2, G2_MUL, &G2_MSM_DISCOUNT), 45_000);
+        assert_eq!(
+            msm_cost(128, G1_MUL, &G1_MSM_DISCOUNT),
+            128 * 12_000 * 519 / 1000
+        );
+        // Past the end of the table the discount stays flat.
+        assert_eq!(
+            msm_cost(200, G2_MUL, &G2_MSM_DISCOUNT),
+            200 * 22_500 * 524 / 1000
+        );
+    }
+
+    #[test]
+    fn test_pairing() {
+        let (g1, g2) = (g1_generator(), g2_generator());
+        let neg_g1 = encode_g1(&-G1Affine::generator());
+
+        // e(P, Q) * e(-P, Q) = 1
+        let pairs = [g1.clone(), g2.clone(), neg_g1, g2.clone()].concat();
+        let output = bls12_pairing(&pairs, 102_900).unwrap();
+        assert_eq!(output.gas_used, 37_700 + 2 * 32_600);
+        assert_eq!(output.output[31], 1);
+
+        // e(P, Q) != 1
+        let output = bls12_pairing(&[g1.clone(), g2.clone()].concat(), 70_300).unwrap();
+        assert_eq!(output.output, vec![0; 32]);
+
+        // A pair with the point at infinity is skipped.
+        let output = bls12_pairing(&[vec![0; 128], g2].concat(), 70_300).unwrap();
+        assert_eq!(output.output[31], 1);
+
+        assert!(bls12_pairing(&[], 100_000).is_err());
+        assert!(bls12_pairing(&g1, 100_000).is_err());
+    }
+
+    #[test]
+    fn test_subgroup_check() {
+        // x = 0 gives y^2 = 4, so (0, 2) is on the curve, but not in the
+        // prime-order subgroup.
+        let mut point = vec![0u8; 128];
+        point[127] = 2;
+        let output = bls12_g1_add(&[point.clone(), vec![0; 128]].concat(), 375);
+        assert_eq!(output.unwrap().output, point);
+
+        let input = [point, scalar(1)].concat();
+        assert!(matches!(
+            bls12_g1_msm(&input, 12_000),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+    }
+
+    /// `encode_to_curve("")` from RFC 9380, J.9.2: the field element the
+    /// message hashes to, and the point it maps to.
+    #[test]
+    fn test_map_fp_to_g1_rfc_vector() {
+        let u = "0x00000000000000000000000000000000156c8a6a2c184569d69a76be144b5cdc\
+                 5141d2d2ca4fe341f011e25e3969c55ad9e9b9ce2eb833c81a908e5fa4ac5f03";
+        let output = bls12_map_fp_to_g1(&hex_to_bytes(u).unwrap(), 5_500).unwrap();
+        assert_eq!(
+            hex::encode(output.output),
+            "00000000000000000000000000000000184bb665c37ff561a89ec2122dd343f2\
+             0e0f4cbcaec84e3c3052ea81d1834e192c426074b02ed3dca4e7676ce4ce48ba\
+             0000000000000000000000000000000004407b8d35af4dacc809927071fc0405\
+             218f1401a6d15af775810e4e460064bcc9468beeba82fdc751be70476c888bf3"
+        );
+    }
+
+    #[test]
+    fn test_map_to_curve() {
+        let output = bls12_map_fp_to_g1(&scalar(1).repeat(2), 5_500);
+        let point = read_g1(&output.unwrap().output, true).unwrap();
+        assert!(!bool::from(point.is_identity()));
+
+        let output = bls12_map_fp2_to_g2(&scalar(1).repeat(4), 23_800).unwrap();
+        assert_eq!(output.gas_used, 23_800);
+        let point = read_g2(&output.output, true).unwrap();
+        assert!(!bool::from(point.is_identity()));
+
+        let mut modulus = vec![0u8; 64];
+        modulus[16..].copy_from_slice(&MODULUS);
+        assert!(matches!(
+            bls12_map_fp_to_g1(&modulus, 5_500),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+        assert_eq!(bls12_map_fp_to_g1(&modulus, 5_499), Err(EvmError::OutOfGas));
+    }
+
+    #[test]
+    fn test_map_fp2_to_g2_vectors() {
+        check_vectors(
+            include_str!("testdata/map_fp2_to_G2_bls.json"),
+            bls12_map_fp2_to_g2,
+        );
+    }
+}
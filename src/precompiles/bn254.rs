@@ -0,0 +1,249 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas};
+use crate::error::{EvmError, Result};
+use crate::spec::SpecId;
+use crate::utils::slice_padded;
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr, G1, G2, Group, Gt};
+use primitive_types::U256;
+
+// Prices from EIP-196/197, cut by EIP-1108 in Istanbul.
+const ADD_BYZANTIUM: u64 = 500;
+const ADD: u64 = 150;
+const MUL_BYZANTIUM: u64 = 40_000;
+const MUL: u64 = 6_000;
+const PAIRING_BYZANTIUM: u64 = 100_000;
+const PAIRING_PAIR_BYZANTIUM: u64 = 80_000;
+const PAIRING: u64 = 45_000;
+const PAIRING_PAIR: u64 = 34_000;
+
+/// A G1 point followed by a G2 point.
+const PAIR_SIZE: usize = 192;
+
+/// 0x06: the sum of two G1 points, from the input `x1 ++ y1 ++ x2 ++ y2`.
+pub fn bn254_add(spec: SpecId, input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = if spec >= SpecId::Istanbul {
+        ADD
+    } else {
+        ADD_BYZANTIUM
+    };
+    check_gas(cost, gas_limit)?;
+
+    let input = slice_padded(input, U256::zero(), 128);
+    let sum = read_g1(&input[..64])? + read_g1(&input[64..])?;
+    Ok(PrecompileOutput::new(cost, encode_g1(sum)))
+}
+
+/// 0x07: a G1 point multiplied by a scalar, from the input `x ++ y ++ s`.
+pub fn bn254_mul(spec: SpecId, input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = if spec >= SpecId::Istanbul {
+        MUL
+    } else {
+        MUL_BYZANTIUM
+    };
+    check_gas(cost, gas_limit)?;
+
+    let input = slice_padded(input, U256::zero(), 96);
+    let point = read_g1(&input[..64])?;
+    let scalar = Fr::from_slice(&input[64..]).expect("scalar is 32 bytes");
+    Ok(PrecompileOutput::new(cost, encode_g1(point * scalar)))
+}
+
+/// 0x08: whether the product of the pairings of a list of (G1, G2) pairs
+/// is one, returned as a 32-byte word. An empty list passes.
+pub fn bn254_pairing(spec: SpecId, input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let pairs = input.len() as u64 / PAIR_SIZE as u64;
+    let cost = if spec >= SpecId::Istanbul {
+        PAIRING + PAIRING_PAIR * pairs
+    } else {
+        PAIRING_BYZANTIUM + PAIRING_PAIR_BYZANTIUM * pairs
+    };
+    check_gas(cost, gas_limit)?;
+
+    if !input.len().is_multiple_of(PAIR_SIZE) {
+        return Err(failure("pairing input is not a list of pairs"));
+    }
+    let mut points = Vec::with_capacity(input.len() / PAIR_SIZE);
+    for pair in input.chunks_exact(PAIR_SIZE) {
+        let g1 = read_g1(&pair[..64])?;
+        let g2 = read_g2(&pair[64..])?;
+        // A pair with the identity contributes a factor of one.
+        if !g1.is_zero() && !g2.is_zero() {
+            points.push((g1, g2));
+        }
+    }
+
+    let success = points.is_empty() || bn::pairing_batch(&points) == Gt::one();
+    let mut output = vec![0u8; 32];
+    output[31] = u8::from(success);
+    Ok(PrecompileOutput::new(cost, output))
+}
+
+fn failure(reason: &str) -> EvmError {
+    EvmError::PrecompileFailure(format!("BN254 {reason}"))
+}
+
+/// A base field element, which must be below the field modulus.
+fn read_fq(bytes: &[u8]) -> Result<Fq> {
+    Fq::from_slice(bytes).map_err(|_| failure("coordinate is not a field element"))
+}
+
+/// A G1 point `x ++ y`, where `(0, 0)` stands for the point at infinity.
+fn read_g1(bytes: &[u8]) -> Result<G1> {
+    let (x, y) = (read_fq(&bytes[..32])?, read_fq(&bytes[32..64])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1::zero());
+    }
+    AffineG1::new(x, y)
+        .map(G1::from)
+        .map_err(|_| failure("G1 point is not on the curve"))
+}
+
+/// A G2 point `x_im ++ x_re ++ y_im ++ y_re`, which must lie in the
+/// prime-order subgroup; all zeros stand for the point at infinity.
+fn read_g2(bytes: &[u8]) -> Result<G2> {
+    let x = Fq2::new(read_fq(&bytes[32..64])?, read_fq(&bytes[..32])?);
+    let y = Fq2::new(read_fq(&bytes[96..128])?, read_fq(&bytes[64..96])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2::zero());
+    }
+    AffineG2::new(x, y)
+        .map(G2::from)
+        .map_err(|_| failure("G2 point is not in the subgroup"))
+}
+
+/// Encodes a G1 point as `x ++ y`, with zeros for the point at infinity.
+fn encode_g1(point: G1) -> Vec<u8> {
+    let mut output = vec![0u8; 64];
+    if let Some(affine) = AffineG1::from_jacobian(point) {
+        let (x, y) = output.split_at_mut(32);
+        affine.x().to_big_endian(x).expect("slice is 32 bytes");
+        affine.y().to_big_endian(y).expect("slice is 32 bytes");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{bytes_to_hex, hex_to_bytes};
+
+    const G1_GENERATOR: &str = "\
+        0000000000000000000000000000000000000000000000000000000000000001\
+        0000000000000000000000000000000000000000000000000000000000000002";
+    const G1_DOUBLE: &str = "0x\
+        030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+        15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+    /// The generator with y replaced by p - y.
+    const G1_NEGATED: &str = "\
+        0000000000000000000000000000000000000000000000000000000000000001\
+        30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    const G2_GENERATOR: &str = "\
+        198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+        1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+        090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+        12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    fn input(parts: &[&str]) -> Vec<u8> {
+        hex_to_bytes(&parts.concat()).unwrap()
+    }
+
+    #[test]
+    fn test_bn254_add() {
+        let output = bn254_add(SpecId::Istanbul, &input(&[G1_GENERATOR, G1_GENERATOR]), 150);
+        let output = output.unwrap();
+        assert_eq!(output.gas_used, 150);
+        assert_eq!(bytes_to_hex(&output.output), G1_DOUBLE);
+
+        // P + (-P) is the point at infinity, and empty input adds two of them.
+        let output = bn254_add(SpecId::Istanbul, &input(&[G1_GENERATOR, G1_NEGATED]), 150);
+        assert_eq!(output.unwrap().output, vec![0; 64]);
+        assert_eq!(
+            bn254_add(SpecId::Istanbul, &[], 150).unwrap().output,
+            vec![0; 64]
+        );
+
+        assert_eq!(
+            bn254_add(SpecId::Byzantium, &[], 499),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_bn254_add_rejects_invalid_points() {
+        // (1, 3) is not on the curve.
+        let mut bad = input(&[G1_GENERATOR, G1_GENERATOR]);
+        bad[63] = 3;
+        assert!(matches!(
+            bn254_add(SpecId::Istanbul, &bad, 150),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+
+        // A coordinate equal to the field modulus.
+        let p = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47";
+        let bad = input(&[p, &G1_GENERATOR[64..]]);
+        assert!(matches!(
+            bn254_add(SpecId::Istanbul, &bad, 150),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+    }
+
+    #[test]
+    fn test_bn254_mul() {
+        let two = format!("{:064x}", 2);
+        let output = bn254_mul(SpecId::Istanbul, &input(&[G1_GENERATOR, &two]), 6000).unwrap();
+        assert_eq!(output.gas_used, 6000);
+        assert_eq!(bytes_to_hex(&output.output), G1_DOUBLE);
+
+        // The group order times any point is the point at infinity.
+        let order = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+        let output = bn254_mul(SpecId::Istanbul, &input(&[G1_GENERATOR, order]), 6000);
+        assert_eq!(output.unwrap().output, vec![0; 64]);
+
+        assert_eq!(
+            bn254_mul(SpecId::Byzantium, &[], 39_999),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_bn254_pairing() {
+        // e(P, Q) * e(-P, Q) = 1
+        let pairs = input(&[G1_GENERATOR, G2_GENERATOR, G1_NEGATED, G2_GENERATOR]);
+        let output = bn254_pairing(SpecId::Istanbul, &pairs, 113_000).unwrap();
+        assert_eq!(output.gas_used, 45_000 + 2 * 34_000);
+        assert_eq!(output.output[31], 1);
+
+        // e(P, Q) * e(P, Q) != 1
+        let pairs = input(&[G1_GENERATOR, G2_GENERATOR, G1_GENERATOR, G2_GENERATOR]);
+        let output = bn254_pairing(SpecId::Istanbul, &pairs, 113_000).unwrap();
+        assert_eq!(output.output, vec![0; 32]);
+
+        let output = bn254_pairing(SpecId::Byzantium, &[], 100_000).unwrap();
+        assert_eq!(output.output[31], 1);
+        assert_eq!(
+            bn254_pairing(SpecId::Byzantium, &pairs, 259_999),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_bn254_pairing_rejects_invalid_input() {
+        let pairs = input(&[G1_GENERATOR, G2_GENERATOR]);
+        assert!(matches!(
+            bn254_pairing(SpecId::Istanbul, &pairs[..191], 100_000),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+
+        // Swapping the halves of x moves the point off the curve.
+        let swapped = format!(
+            "{}{}{}",
+            &G2_GENERATOR[64..128],
+            &G2_GENERATOR[..64],
+            &G2_GENERATOR[128..]
+        );
+        let pairs = input(&[G1_GENERATOR, &swapped]);
+        assert!(matches!(
+            bn254_pairing(SpecId::Istanbul, &pairs, 100_000),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas};
+use crate::utils::{keccak256, slice_padded};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use primitive_types::U256;
+
+const ECRECOVER: u64 = 3000;
+
+/// 0x01: the address that signed a message hash, from the input
+/// `hash ++ v ++ r ++ s`. An invalid signature is not an error: the call
+/// succeeds with empty output.
+pub fn ecrecover(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    check_gas(ECRECOVER, gas_limit)?;
+    let input = slice_padded(input, U256::zero(), 128);
+    let output = recover_address(&input).unwrap_or_default();
+    Ok(PrecompileOutput::new(ECRECOVER, output))
+}
+
+/// Returns the signer left-padded to 32 bytes, or `None` for an invalid
+/// signature.
+fn recover_address(input: &[u8]) -> Option<Vec<u8>> {
+    let (hash, v, signature) = (&input[..32], &input[32..64], &input[64..128]);
+
+    // v is a full word that must be exactly 27 or 28.
+    if v[..31].iter().any(|&byte| byte != 0) || !matches!(v[31], 27 | 28) {
+        return None;
+    }
+    let mut recovery_id = v[31] - 27;
+
+    // r and s must both lie in [1, n); high s values are allowed, so flip
+    // them into the lower half the library expects.
+    let mut signature = Signature::from_slice(signature).ok()?;
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+        recovery_id ^= 1;
+    }
+
+    let key =
+        VerifyingKey::recover_from_prehash(hash, &signature, RecoveryId::from_byte(recovery_id)?)
+            .ok()?;
+    let point = key.to_encoded_point(false);
+
+    let mut output = vec![0u8; 12];
+    output.extend_from_slice(&keccak256(&point.as_bytes()[1..])[12..]);
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvmError;
+    use crate::utils::{bytes_to_hex, hex_to_bytes};
+
+    const INPUT: &str = "0x18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c\
+        000000000000000000000000000000000000000000000000000000000000001c\
+        73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f\
+        eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549";
+
+    #[test]
+    fn test_ecrecover() {
+        let output = ecrecover(&hex_to_bytes(INPUT).unwrap(), 3000).unwrap();
+        assert_eq!(output.gas_used, 3000);
+        assert_eq!(
+            bytes_to_hex(&output.output),
+            "0x000000000000000000000000a94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+        );
+    }
+
+    #[test]
+    fn test_ecrecover_invalid_signature() {
+        let mut input = hex_to_bytes(INPUT).unwrap();
+        input[63] = 29;
+        assert_eq!(
+            ecrecover(&input, 3000),
+            Ok(PrecompileOutput::new(3000, Vec::new()))
+        );
+        assert_eq!(ecrecover(&input, 2999), Err(EvmError::OutOfGas));
+
+        // Short input is padded with zeros, leaving r = s = 0.
+        input[63] = 28;
+        assert_eq!(
+            ecrecover(&input[..64], 3000).unwrap().output,
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn test_ecrecover_high_s() {
+        // The same signature with s replaced by n - s and v flipped.
+        let mut input = hex_to_bytes(INPUT).unwrap();
+        let n = U256::from_big_endian(
+            &hex_to_bytes("0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+                .unwrap(),
+        );
+        let s = U256::from_big_endian(&input[96..128]);
+        input[96..128].copy_from_slice(&(n - s).to_big_endian());
+        input[63] = 27;
+        assert_eq!(
+            bytes_to_hex(&ecrecover(&input, 3000).unwrap().output),
+            "0x000000000000000000000000a94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+        );
+    }
+}
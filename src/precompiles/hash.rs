@@ -0,0 +1,52 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas, linear_cost};
+use crate::utils;
+
+const SHA256_BASE: u64 = 60;
+const SHA256_WORD: u64 = 12;
+const RIPEMD160_BASE: u64 = 600;
+const RIPEMD160_WORD: u64 = 120;
+
+/// 0x02: the SHA-256 hash of the input.
+pub fn sha256(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = linear_cost(input, SHA256_BASE, SHA256_WORD);
+    check_gas(cost, gas_limit)?;
+    Ok(PrecompileOutput::new(cost, utils::sha256(input).to_vec()))
+}
+
+/// 0x03: the RIPEMD-160 hash of the input, left-padded to 32 bytes.
+pub fn ripemd160(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = linear_cost(input, RIPEMD160_BASE, RIPEMD160_WORD);
+    check_gas(cost, gas_limit)?;
+    let mut output = vec![0u8; 12];
+    output.extend_from_slice(&utils::ripemd160(input));
+    Ok(PrecompileOutput::new(cost, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvmError;
+    use crate::utils::bytes_to_hex;
+
+    #[test]
+    fn test_sha256() {
+        let output = sha256(b"abc", 72).unwrap();
+        assert_eq!(output.gas_used, 72);
+        assert_eq!(
+            bytes_to_hex(&output.output),
+            "0xba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(sha256(b"abc", 71), Err(EvmError::OutOfGas));
+    }
+
+    #[test]
+    fn test_ripemd160() {
+        let output = ripemd160(b"abc", 720).unwrap();
+        assert_eq!(output.gas_used, 720);
+        assert_eq!(
+            bytes_to_hex(&output.output),
+            "0x0000000000000000000000008eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(ripemd160(b"abc", 719), Err(EvmError::OutOfGas));
+    }
+}
@@ -0,0 +1,26 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas, linear_cost};
+
+const IDENTITY_BASE: u64 = 15;
+const IDENTITY_WORD: u64 = 3;
+
+/// 0x04: returns its input unchanged.
+pub fn identity(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let cost = linear_cost(input, IDENTITY_BASE, IDENTITY_WORD);
+    check_gas(cost, gas_limit)?;
+    Ok(PrecompileOutput::new(cost, input.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EvmError;
+
+    #[test]
+    fn test_identity() {
+        let input = [0xab; 33];
+        let output = identity(&input, 21).unwrap();
+        assert_eq!(output, PrecompileOutput::new(21, input.to_vec()));
+        assert_eq!(identity(&input, 20), Err(EvmError::OutOfGas));
+        assert_eq!(identity(&[], 15).unwrap().gas_used, 15);
+    }
+}
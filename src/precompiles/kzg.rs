@@ -0,0 +1,182 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas};
+use crate::error::{EvmError, Result};
+use crate::utils::sha256;
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Prepared, Gt, Scalar, multi_miller_loop};
+use std::sync::OnceLock;
+
+/// `versioned_hash ++ z ++ y ++ commitment ++ proof`
+const INPUT_SIZE: usize = 192;
+const POINT_EVALUATION: u64 = 50_000;
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+const FIELD_ELEMENTS_PER_BLOB: u16 = 4096;
+/// Order of the BLS12-381 scalar field, big-endian.
+const BLS_MODULUS: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// `[tau]G2`, compressed: `g2_monomial[1]` of the Ethereum KZG ceremony
+/// output, the line after the G2 generator in the `trusted_setup.txt` that
+/// c-kzg-4844 ships. It is the only point of the setup that checking a
+/// single evaluation needs.
+const TAU_G2: [u8; 96] = [
+    0xb5, 0xbf, 0xd7, 0xdd, 0x8c, 0xde, 0xb1, 0x28, 0x84, 0x3b, 0xc2, 0x87, 0x23, 0x0a, 0xf3, 0x89,
+    0x26, 0x18, 0x70, 0x75, 0xcb, 0xfb, 0xef, 0xa8, 0x10, 0x09, 0xa2, 0xce, 0x61, 0x5a, 0xc5, 0x3d,
+    0x29, 0x14, 0xe5, 0x87, 0x0c, 0xb4, 0x52, 0xd2, 0xaf, 0xaa, 0xab, 0x24, 0xf3, 0x49, 0x9f, 0x72,
+    0x18, 0x5c, 0xbf, 0xee, 0x53, 0x49, 0x27, 0x14, 0x73, 0x44, 0x29, 0xb7, 0xb3, 0x86, 0x08, 0xe2,
+    0x39, 0x26, 0xc9, 0x11, 0xcc, 0xec, 0xea, 0xc9, 0xa3, 0x68, 0x51, 0x47, 0x7b, 0xa4, 0xc6, 0x0b,
+    0x08, 0x70, 0x41, 0xde, 0x62, 0x10, 0x00, 0xed, 0xc9, 0x8e, 0xda, 0xda, 0x20, 0xc1, 0xde, 0xf2,
+];
+
+/// 0x0a: verifies that the blob committed to by `commitment` evaluates to
+/// `y` at `z` (EIP-4844), and that `versioned_hash` names that commitment.
+pub fn point_evaluation(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    check_gas(POINT_EVALUATION, gas_limit)?;
+    if input.len() != INPUT_SIZE {
+        return Err(failure("input must be 192 bytes"));
+    }
+
+    let (versioned_hash, z, y) = (&input[..32], &input[32..64], &input[64..96]);
+    let (commitment, proof) = (&input[96..144], &input[144..192]);
+    if versioned_hash != kzg_to_versioned_hash(commitment) {
+        return Err(failure("versioned hash does not match the commitment"));
+    }
+
+    if !verify_kzg_proof(
+        &read_g1(commitment)?,
+        &read_scalar(z)?,
+        &read_scalar(y)?,
+        &read_g1(proof)?,
+    ) {
+        return Err(failure("proof does not verify"));
+    }
+
+    // FIELD_ELEMENTS_PER_BLOB ++ BLS_MODULUS, each a 32-byte word.
+    let mut output = vec![0u8; 64];
+    output[30..32].copy_from_slice(&FIELD_ELEMENTS_PER_BLOB.to_be_bytes());
+    output[32..].copy_from_slice(&BLS_MODULUS);
+    Ok(PrecompileOutput::new(POINT_EVALUATION, output))
+}
+
+/// `0x01 ++ sha256(commitment)[1..]`, how transactions refer to a blob.
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> [u8; 32] {
+    let mut hash = sha256(commitment);
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Checks `e(commitment - [y]G1, G2) == e(proof, [tau]G2 - [z]G2)`: the
+/// polynomial minus `y` is divisible by `X - z`.
+fn verify_kzg_proof(commitment: &G1Affine, z: &Scalar, y: &Scalar, proof: &G1Affine) -> bool {
+    let g2 = G2Affine::generator();
+    let shifted_commitment =
+        G1Affine::from(G1Projective::from(commitment) - G1Affine::generator() * y);
+    let shifted_tau = G2Affine::from(tau_g2() - g2 * z);
+
+    let pairing = multi_miller_loop(&[
+        (&shifted_commitment, &G2Prepared::from(-g2)),
+        (proof, &G2Prepared::from(shifted_tau)),
+    ]);
+    pairing.final_exponentiation() == Gt::identity()
+}
+
+fn tau_g2() -> G2Affine {
+    static TAU_G2_POINT: OnceLock<G2Affine> = OnceLock::new();
+    *TAU_G2_POINT.get_or_init(|| {
+        Option::from(G2Affine::from_compressed(&TAU_G2)).expect("[tau]G2 is a valid point")
+    })
+}
+
+fn failure(reason: &str) -> EvmError {
+    EvmError::PrecompileFailure(format!("KZG point evaluation {reason}"))
+}
+
+/// A compressed G1 point, which must lie in the prime-order subgroup.
+fn read_g1(bytes: &[u8]) -> Result<G1Affine> {
+    let bytes: [u8; 48] = bytes.try_into().expect("slice is 48 bytes");
+    Option::from(G1Affine::from_compressed(&bytes)).ok_or_else(|| failure("point is invalid"))
+}
+
+/// A big-endian field element, which must be below the BLS modulus.
+fn read_scalar(bytes: &[u8]) -> Result<Scalar> {
+    let mut bytes: [u8; 32] = bytes.try_into().expect("slice is 32 bytes");
+    bytes.reverse();
+    Option::from(Scalar::from_bytes(&bytes)).ok_or_else(|| failure("field element is invalid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{bytes_to_hex, hex_to_bytes};
+
+    // The EIP-4844 point evaluation vector that clients test against.
+    const COMMITMENT: &str = "8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7";
+    const Z: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000";
+    const Y: &str = "1522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e9";
+    const PROOF: &str = "a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c";
+
+    fn input(z: &str, y: &str) -> Vec<u8> {
+        let commitment = hex_to_bytes(COMMITMENT).unwrap();
+        let mut input = kzg_to_versioned_hash(&commitment).to_vec();
+        input.extend(hex_to_bytes(&[z, y, COMMITMENT, PROOF].concat()).unwrap());
+        input
+    }
+
+    #[test]
+    fn test_point_evaluation() {
+        let output = point_evaluation(&input(Z, Y), 50_000).unwrap();
+        assert_eq!(output.gas_used, 50_000);
+        assert_eq!(
+            bytes_to_hex(&output.output),
+            "0x0000000000000000000000000000000000000000000000000000000000001000\
+             73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+        );
+        assert_eq!(
+            point_evaluation(&input(Z, Y), 49_999),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    /// `[tau]G1`, compressed: `g1_monomial[1]` of the same ceremony output.
+    const TAU_G1: &str = "ad3eb50121139aa34db1d545093ac9374ab7bca2c0f3bf28e27c8dcd8fc7cb42d25926fc0c97b336e9f0fb35e5a04c81";
+
+    #[test]
+    fn test_tau_g2_matches_ceremony() {
+        // Commit to p(X) = 3 + 5X with the published [tau]G1 and open it at
+        // z = 7: y = 38 and the quotient (p(X) - y) / (X - z) is just 5. The
+        // proof only checks out if TAU_G2 holds the same tau.
+        let tau_g1: [u8; 48] = hex_to_bytes(TAU_G1).unwrap().try_into().unwrap();
+        let tau_g1 = G1Affine::from_compressed(&tau_g1).unwrap();
+        let g1 = G1Affine::generator();
+        let (a, b, z) = (Scalar::from(3), Scalar::from(5), Scalar::from(7));
+        let commitment = G1Affine::from(g1 * a + tau_g1 * b);
+        let proof = G1Affine::from(g1 * b);
+
+        assert!(verify_kzg_proof(&commitment, &z, &(a + b * z), &proof));
+        assert!(!verify_kzg_proof(
+            &commitment,
+            &z,
+            &(a + b * z + Scalar::one()),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_point_evaluation_rejects_wrong_claims() {
+        // A different evaluation.
+        let wrong_y = "1522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49ea";
+        assert!(point_evaluation(&input(Z, wrong_y), 50_000).is_err());
+
+        // A versioned hash for some other commitment.
+        let mut bad = input(Z, Y);
+        bad[0] = 0x02;
+        assert!(point_evaluation(&bad, 50_000).is_err());
+
+        // z equal to the modulus is not a field element.
+        let modulus = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+        assert!(point_evaluation(&input(modulus, Y), 50_000).is_err());
+
+        assert!(point_evaluation(&input(Z, Y)[..191], 50_000).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Contracts implemented natively rather than in bytecode, living at fixed
+//! low addresses. A call to one of them runs the Rust implementation in
+//! place of the callee's code.
+
+mod blake2;
+mod bls12_381;
+mod bn254;
+mod ecrecover;
+mod hash;
+mod identity;
+mod kzg;
+mod modexp;
+
+pub use blake2::blake2f;
+pub use bls12_381::{
+    bls12_g1_add, bls12_g1_msm, bls12_g2_add, bls12_g2_msm, bls12_map_fp_to_g1,
+    bls12_map_fp2_to_g2, bls12_pairing,
+};
+pub use bn254::{bn254_add, bn254_mul, bn254_pairing};
+pub use ecrecover::ecrecover;
+pub use hash::{ripemd160, sha256};
+pub use identity::identity;
+pub use kzg::{kzg_to_versioned_hash, point_evaluation};
+pub use modexp::{modexp, modexp_cost};
+
+use crate::context::Address;
+use crate::error::{EvmError, Result};
+use crate::spec::SpecId;
+use primitive_types::H160;
+use std::collections::HashMap;
+use std::fmt;
+
+pub const ECRECOVER: Address = precompile_address(0x01);
+pub const SHA256: Address = precompile_address(0x02);
+pub const RIPEMD160: Address = precompile_address(0x03);
+pub const IDENTITY: Address = precompile_address(0x04);
+pub const MODEXP: Address = precompile_address(0x05);
+pub const BN254_ADD: Address = precompile_address(0x06);
+pub const BN254_MUL: Address = precompile_address(0x07);
+pub const BN254_PAIRING: Address = precompile_address(0x08);
+pub const BLAKE2F: Address = precompile_address(0x09);
+pub const POINT_EVALUATION: Address = precompile_address(0x0a);
+pub const BLS12_G1ADD: Address = precompile_address(0x0b);
+pub const BLS12_G1MSM: Address = precompile_address(0x0c);
+pub const BLS12_G2ADD: Address = precompile_address(0x0d);
+pub const BLS12_G2MSM: Address = precompile_address(0x0e);
+pub const BLS12_PAIRING: Address = precompile_address(0x0f);
+pub const BLS12_MAP_FP_TO_G1: Address = precompile_address(0x10);
+pub const BLS12_MAP_FP2_TO_G2: Address = precompile_address(0x11);
+
+/// Address of the precompile numbered `index`.
+pub const fn precompile_address(index: u8) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[19] = index;
+    H160(bytes)
+}
+
+/// Output of a precompile that ran to completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecompileOutput {
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+}
+
+impl PrecompileOutput {
+    pub fn new(gas_used: u64, output: Vec<u8>) -> Self {
+        Self { gas_used, output }
+    }
+}
+
+/// A failed precompile consumes all gas given to the call, like an
+/// exceptional halt.
+pub type PrecompileResult = Result<PrecompileOutput>;
+
+/// A natively implemented contract. It is given the calldata and the gas
+/// forwarded to the call, and must fail with [`EvmError::OutOfGas`] rather
+/// than report spending more than `gas_limit`.
+pub trait Precompile {
+    fn run(&self, input: &[u8], gas_limit: u64) -> PrecompileResult;
+}
+
+impl<F> Precompile for F
+where
+    F: Fn(&[u8], u64) -> PrecompileResult,
+{
+    fn run(&self, input: &[u8], gas_limit: u64) -> PrecompileResult {
+        self(input, gas_limit)
+    }
+}
+
+/// The precompiles a call can reach, keyed by address.
+pub struct Precompiles {
+    contracts: HashMap<Address, Box<dyn Precompile>>,
+}
+
+impl Precompiles {
+    /// The precompiles that exist under `spec`.
+    pub fn new(spec: SpecId) -> Self {
+        let mut precompiles = Self::empty();
+        precompiles.insert(ECRECOVER, ecrecover);
+        precompiles.insert(SHA256, sha256);
+        precompiles.insert(RIPEMD160, ripemd160);
+        precompiles.insert(IDENTITY, identity);
+        if spec >= SpecId::Byzantium {
+            precompiles.insert(MODEXP, move |input: &[u8], gas_limit| {
+                modexp(spec, input, gas_limit)
+            });
+            precompiles.insert(BN254_ADD, move |input: &[u8], gas_limit| {
+                bn254_add(spec, input, gas_limit)
+            });
+            precompiles.insert(BN254_MUL, move |input: &[u8], gas_limit| {
+                bn254_mul(spec, input, gas_limit)
+            });
+            precompiles.insert(BN254_PAIRING, move |input: &[u8], gas_limit| {
+                bn254_pairing(spec, input, gas_limit)
+            });
+        }
+        if spec >= SpecId::Istanbul {
+            precompiles.insert(BLAKE2F, blake2f);
+        }
+        if spec >= SpecId::Cancun {
+            precompiles.insert(POINT_EVALUATION, point_evaluation);
+        }
+        if spec >= SpecId::Prague {
+            precompiles.insert(BLS12_G1ADD, bls12_g1_add);
+            precompiles.insert(BLS12_G1MSM, bls12_g1_msm);
+            precompiles.insert(BLS12_G2ADD, bls12_g2_add);
+            precompiles.insert(BLS12_G2MSM, bls12_g2_msm);
+            precompiles.insert(BLS12_PAIRING, bls12_pairing);
+            precompiles.insert(BLS12_MAP_FP_TO_G1, bls12_map_fp_to_g1);
+            precompiles.insert(BLS12_MAP_FP2_TO_G2, bls12_map_fp2_to_g2);
+        }
+        precompiles
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            contracts: HashMap::new(),
+        }
+    }
+
+    /// Installs `precompile` at `address`, replacing whatever was there.
+    pub fn insert(&mut self, address: Address, precompile: impl Precompile + 'static) {
+        self.contracts.insert(address, Box::new(precompile));
+    }
+
+    pub fn remove(&mut self, address: &Address) {
+        self.contracts.remove(address);
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&dyn Precompile> {
+        self.contracts.get(address).map(Box::as_ref)
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.contracts.contains_key(address)
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.contracts.keys()
+    }
+}
+
+impl Default for Precompiles {
+    fn default() -> Self {
+        Self::new(SpecId::default())
+    }
+}
+
+impl fmt::Debug for Precompiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut addresses: Vec<_> = self.addresses().collect();
+        addresses.sort();
+        f.debug_struct("Precompiles")
+            .field("addresses", &addresses)
+            .finish()
+    }
+}
+
+/// Gas of a precompile priced per 32-byte word of input.
+pub(crate) fn linear_cost(input: &[u8], base: u64, word: u64) -> u64 {
+    base + word * input.len().div_ceil(32) as u64
+}
+
+/// Fails with out of gas unless `cost` fits within `gas_limit`.
+pub(crate) fn check_gas(cost: u64, gas_limit: u64) -> Result<()> {
+    if cost > gas_limit {
+        return Err(EvmError::OutOfGas);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let mut precompiles = Precompiles::new(SpecId::Frontier);
+        assert!(precompiles.contains(&IDENTITY));
+        assert!(!precompiles.contains(&precompile_address(0x42)));
+
+        precompiles.insert(precompile_address(0x42), |input: &[u8], _gas_limit| {
+            Ok(PrecompileOutput::new(
+                1,
+                input.iter().rev().copied().collect(),
+            ))
+        });
+        let output = precompiles
+            .get(&precompile_address(0x42))
+            .unwrap()
+            .run(&[1, 2, 3], 10)
+            .unwrap();
+        assert_eq!(output, PrecompileOutput::new(1, vec![3, 2, 1]));
+
+        assert!(!Precompiles::new(SpecId::Homestead).contains(&MODEXP));
+        assert!(Precompiles::new(SpecId::Byzantium).contains(&MODEXP));
+        assert!(!Precompiles::new(SpecId::Petersburg).contains(&BLAKE2F));
+        assert!(!Precompiles::new(SpecId::Shanghai).contains(&POINT_EVALUATION));
+        assert!(Precompiles::new(SpecId::Cancun).contains(&POINT_EVALUATION));
+        assert!(!Precompiles::new(SpecId::Cancun).contains(&BLS12_G1ADD));
+        assert!(Precompiles::new(SpecId::Prague).contains(&BLS12_MAP_FP2_TO_G2));
+
+        precompiles.remove(&IDENTITY);
+        assert!(precompiles.get(&IDENTITY).is_none());
+    }
+
+    #[test]
+    fn test_check_gas() {
+        assert_eq!(check_gas(15, 15), Ok(()));
+        assert_eq!(check_gas(16, 15), Err(EvmError::OutOfGas));
+    }
+}
@@ -0,0 +1,475 @@
+use super::{PrecompileOutput, PrecompileResult, check_gas};
+use crate::error::EvmError;
+use crate::spec::SpecId;
+use crate::utils::slice_padded;
+use primitive_types::U256;
+use std::cmp::Ordering;
+
+/// Divisor of the original pricing (EIP-198).
+const QUAD_DIVISOR_BYZANTIUM: u64 = 20;
+/// Divisor of the repriced formula (EIP-2565).
+const QUAD_DIVISOR_BERLIN: u64 = 3;
+const MIN_GAS_BERLIN: u64 = 200;
+const MIN_GAS_OSAKA: u64 = 500;
+/// Largest base, exponent or modulus accepted since EIP-7823.
+const MAX_INPUT_SIZE: u64 = 1024;
+
+/// 0x05: `base ** exponent % modulus` over integers of any size, from the
+/// input `base_len ++ exp_len ++ mod_len ++ base ++ exponent ++ modulus`
+/// where the lengths are 32-byte words and missing bytes read as zero.
+pub fn modexp(spec: SpecId, input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let base_len = read_length(input, 0);
+    let exp_len = read_length(input, 32);
+    let mod_len = read_length(input, 64);
+
+    if spec >= SpecId::Osaka
+        && [base_len, exp_len, mod_len]
+            .iter()
+            .any(|&len| len > MAX_INPUT_SIZE)
+    {
+        return Err(EvmError::PrecompileFailure(
+            "modexp input exceeds 1024 bytes".to_string(),
+        ));
+    }
+
+    // Only the leading 32 bytes of the exponent feed into the price.
+    let exp_offset = U256::from(96) + U256::from(base_len);
+    let exp_head = slice_padded(input, exp_offset, exp_len.min(32) as usize);
+    let exp_head = U256::from_big_endian(&exp_head);
+
+    let cost = modexp_cost(spec, base_len, exp_len, mod_len, exp_head).ok_or(EvmError::OutOfGas)?;
+    check_gas(cost, gas_limit)?;
+
+    if mod_len == 0 {
+        return Ok(PrecompileOutput::new(cost, Vec::new()));
+    }
+    // Only the bytes actually present are read, as the lengths alone can
+    // ask for far more than the input holds. Anything missing is zero, so a
+    // modulus cut short ends in zeros, and one that isn't zero altogether
+    // means the base and exponent before it are complete.
+    let operands = input.get(96..).unwrap_or_default();
+    let (base, operands) = split_at_most(operands, base_len);
+    let (exponent, operands) = split_at_most(operands, exp_len);
+    let (modulus, _) = split_at_most(operands, mod_len);
+    let mod_len = mod_len as usize;
+    if modulus.iter().all(|&byte| byte == 0) {
+        return Ok(PrecompileOutput::new(cost, vec![0; mod_len]));
+    }
+
+    // The price covers `mod_len`, which the output takes up anyway.
+    let mut modulus = modulus.to_vec();
+    modulus.resize(mod_len, 0);
+    let output = BigUint::from_be_bytes(base)
+        .pow_mod(exponent, &BigUint::from_be_bytes(&modulus))
+        .to_be_bytes(mod_len);
+    Ok(PrecompileOutput::new(cost, output))
+}
+
+/// Splits off the first `len` bytes, or all of them if there are fewer.
+fn split_at_most(bytes: &[u8], len: u64) -> (&[u8], &[u8]) {
+    bytes.split_at(len.min(bytes.len() as u64) as usize)
+}
+
+/// Reads the length word at `offset`, saturating at `u64::MAX`.
+fn read_length(input: &[u8], offset: usize) -> u64 {
+    let word = U256::from_big_endian(&slice_padded(input, U256::from(offset), 32));
+    if word > U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        word.as_u64()
+    }
+}
+
+/// Price of a modexp call: the cost of one multiplication at the operand
+/// size times the number of squarings the exponent implies. EIP-2565
+/// rescaled it for Berlin and EIP-7883 raised it for Osaka. `None` if the
+/// price doesn't even fit in a `u64`.
+pub fn modexp_cost(
+    spec: SpecId,
+    base_len: u64,
+    exp_len: u64,
+    mod_len: u64,
+    exp_head: U256,
+) -> Option<u64> {
+    let max_len = U256::from(base_len.max(mod_len));
+    let head_bits = U256::from(exp_head.bits().saturating_sub(1));
+    let tail_len = U256::from(exp_len.saturating_sub(32));
+
+    let cost = if spec >= SpecId::Osaka {
+        let complexity = if max_len <= U256::from(32) {
+            U256::from(16)
+        } else {
+            let words = (max_len + 7) / 8;
+            U256::from(2) * words * words
+        };
+        let iterations = (U256::from(16) * tail_len + head_bits).max(U256::one());
+        (complexity * iterations).max(U256::from(MIN_GAS_OSAKA))
+    } else if spec >= SpecId::Berlin {
+        let words = (max_len + 7) / 8;
+        let iterations = (U256::from(8) * tail_len + head_bits).max(U256::one());
+        (words * words * iterations / QUAD_DIVISOR_BERLIN).max(U256::from(MIN_GAS_BERLIN))
+    } else {
+        let x = max_len;
+        let complexity = if x <= U256::from(64) {
+            x * x
+        } else if x <= U256::from(1024) {
+            x * x / 4 + U256::from(96) * x - U256::from(3072)
+        } else {
+            x * x / 16 + U256::from(480) * x - U256::from(199_680)
+        };
+        let iterations = (U256::from(8) * tail_len + head_bits).max(U256::one());
+        complexity * iterations / QUAD_DIVISOR_BYZANTIUM
+    };
+
+    (cost <= U256::from(u64::MAX)).then(|| cost.as_u64())
+}
+
+/// Unsigned integer of any size: little-endian 32-bit limbs with no
+/// trailing zero limbs, so zero is the empty vector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BigUint(Vec<u32>);
+
+impl BigUint {
+    fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self(limbs)
+    }
+
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(4)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0u32, |limb, &byte| (limb << 8) | u32::from(byte))
+            })
+            .collect();
+        Self::from_limbs(limbs)
+    }
+
+    /// Big-endian bytes, left-padded to `len`. The value must fit.
+    fn to_be_bytes(&self, len: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.0.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        bytes.resize(len.max(bytes.len()), 0);
+        bytes.truncate(len);
+        bytes.reverse();
+        bytes
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut limbs = vec![0u32; self.0.len() + other.0.len()];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.0.iter().enumerate() {
+                let t = u64::from(a) * u64::from(b) + u64::from(limbs[i + j]) + carry;
+                limbs[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            limbs[i + other.0.len()] = carry as u32;
+        }
+        Self::from_limbs(limbs)
+    }
+
+    /// Remainder of the division by a non-zero `divisor`, by long division
+    /// (Knuth's algorithm D).
+    fn rem(&self, divisor: &Self) -> Self {
+        if self.cmp(divisor) == Ordering::Less {
+            return self.clone();
+        }
+        if let [single] = divisor.0[..] {
+            let remainder = self.0.iter().rev().fold(0u64, |remainder, &limb| {
+                ((remainder << 32) | u64::from(limb)) % u64::from(single)
+            });
+            return Self::from_limbs(vec![remainder as u32]);
+        }
+
+        // Shift both sides so the divisor's top limb has its high bit set,
+        // which keeps each estimated quotient digit at most two too large.
+        let shift = divisor
+            .0
+            .last()
+            .expect("divisor is non-zero")
+            .leading_zeros();
+        let v = shl_limbs(&divisor.0, shift);
+        let mut u = shl_limbs(&self.0, shift);
+        u.push(0);
+        let n = v.len();
+        let (v_top, v_next) = (u64::from(v[n - 1]), u64::from(v[n - 2]));
+
+        for j in (0..u.len() - n).rev() {
+            let numerator = (u64::from(u[j + n]) << 32) | u64::from(u[j + n - 1]);
+            let mut q = numerator / v_top;
+            let mut r = numerator % v_top;
+            while q > u64::from(u32::MAX) || q * v_next > ((r << 32) | u64::from(u[j + n - 2])) {
+                q -= 1;
+                r += v_top;
+                if r > u64::from(u32::MAX) {
+                    break;
+                }
+            }
+
+            // u[j..=j + n] -= q * v
+            let mut borrow = 0i64;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let product = q * u64::from(v[i]) + carry;
+                carry = product >> 32;
+                let t = i64::from(u[i + j]) - borrow - (product & 0xffff_ffff) as i64;
+                u[i + j] = t as u32;
+                borrow = i64::from(t < 0);
+            }
+            let t = i64::from(u[j + n]) - borrow - carry as i64;
+            u[j + n] = t as u32;
+
+            // The estimate was one too large: add the divisor back.
+            if t < 0 {
+                let mut carry = 0u64;
+                for i in 0..n {
+                    let sum = u64::from(u[i + j]) + u64::from(v[i]) + carry;
+                    u[i + j] = sum as u32;
+                    carry = sum >> 32;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u32);
+            }
+        }
+
+        u.truncate(n);
+        Self::from_limbs(shr_limbs(&u, shift))
+    }
+
+    /// `self ** exponent % modulus`, with the exponent given as big-endian
+    /// bytes. A zero modulus yields zero.
+    fn pow_mod(&self, exponent: &[u8], modulus: &Self) -> Self {
+        if modulus.is_zero() {
+            return Self(Vec::new());
+        }
+        let base = self.rem(modulus);
+        let mut result = Self::from_limbs(vec![1]).rem(modulus);
+        for byte in exponent {
+            for bit in (0..8).rev() {
+                result = result.mul(&result).rem(modulus);
+                if byte >> bit & 1 == 1 {
+                    result = result.mul(&base).rem(modulus);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+fn shl_limbs(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = Vec::with_capacity(limbs.len() + 1);
+    let mut carry = 0;
+    for &limb in limbs {
+        shifted.push((limb << shift) | carry);
+        carry = limb >> (32 - shift);
+    }
+    if carry != 0 {
+        shifted.push(carry);
+    }
+    shifted
+}
+
+fn shr_limbs(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = vec![0; limbs.len()];
+    for i in 0..limbs.len() {
+        let high = limbs.get(i + 1).map_or(0, |&limb| limb << (32 - shift));
+        shifted[i] = (limbs[i] >> shift) | high;
+    }
+    shifted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{bytes_to_hex, hex_to_bytes};
+    use primitive_types::U512;
+
+    /// 3 ** (p - 1) % p for the secp256k1 field prime p (EIP-198).
+    const FERMAT: &str = "0x\
+        0000000000000000000000000000000000000000000000000000000000000001\
+        0000000000000000000000000000000000000000000000000000000000000020\
+        0000000000000000000000000000000000000000000000000000000000000020\
+        03\
+        fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+        fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+
+    #[test]
+    fn test_modexp() {
+        let input = hex_to_bytes(FERMAT).unwrap();
+        let output = modexp(SpecId::Byzantium, &input, 13056).unwrap();
+        assert_eq!(output.gas_used, 13056);
+        assert_eq!(
+            bytes_to_hex(&output.output),
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            modexp(SpecId::Byzantium, &input, 13055),
+            Err(EvmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_modexp_zero_modulus() {
+        // The second EIP-198 example: the modulus is zero.
+        let mut input = hex_to_bytes(FERMAT).unwrap();
+        input[129..].fill(0);
+        let output = modexp(SpecId::Prague, &input, 1360).unwrap();
+        assert_eq!(output.output, vec![0; 32]);
+    }
+
+    #[test]
+    fn test_modexp_short_input() {
+        let header = |base_len: u64, exp_len: u64, mod_len: u64| {
+            [base_len, exp_len, mod_len]
+                .iter()
+                .flat_map(|len| U256::from(*len).to_big_endian())
+                .collect::<Vec<u8>>()
+        };
+
+        // A terabyte-long exponent that is barely there: the modulus after
+        // it is missing, hence zero, and nothing near that size is read.
+        let mut input = header(1, 1 << 40, 1);
+        input.extend([0x02, 0xff, 0xff]);
+        let output = modexp(SpecId::Berlin, &input, u64::MAX).unwrap();
+        assert_eq!(output.output, vec![0]);
+
+        // A modulus cut short ends in zeros: 3 ** 2 % 0x0100.
+        let mut input = header(1, 1, 2);
+        input.extend([0x03, 0x02, 0x01]);
+        let output = modexp(SpecId::Berlin, &input, u64::MAX).unwrap();
+        assert_eq!(output.output, vec![0x00, 0x09]);
+    }
+
+    #[test]
+    fn test_modexp_cost_by_fork() {
+        let head = U256::from_big_endian(
+            &hex_to_bytes("0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e")
+                .unwrap(),
+        );
+        assert_eq!(modexp_cost(SpecId::Byzantium, 1, 32, 32, head), Some(13056));
+        assert_eq!(modexp_cost(SpecId::Berlin, 1, 32, 32, head), Some(1360));
+        assert_eq!(modexp_cost(SpecId::Osaka, 1, 32, 32, head), Some(4080));
+
+        // Minimums, and the long-exponent and large-operand terms.
+        assert_eq!(
+            modexp_cost(SpecId::Byzantium, 0, 0, 0, U256::zero()),
+            Some(0)
+        );
+        assert_eq!(modexp_cost(SpecId::Berlin, 1, 1, 1, U256::one()), Some(200));
+        assert_eq!(modexp_cost(SpecId::Osaka, 1, 1, 1, U256::one()), Some(500));
+        assert_eq!(
+            modexp_cost(SpecId::Berlin, 96, 33, 96, U256::one()),
+            Some(144 * 8 / 3)
+        );
+        assert_eq!(
+            modexp_cost(SpecId::Osaka, 64, 33, 64, U256::one()),
+            Some(128 * 16)
+        );
+        assert_eq!(
+            modexp_cost(SpecId::Byzantium, 100, 1, 100, U256::from(2)),
+            Some((100 * 100 / 4 + 96 * 100 - 3072) / 20)
+        );
+    }
+
+    #[test]
+    fn test_modexp_oversized_lengths() {
+        // A modulus of 2^64 - 1 bytes can never be paid for.
+        let mut input = vec![0u8; 96];
+        input[88..96].fill(0xff);
+        assert_eq!(
+            modexp(SpecId::Prague, &input, u64::MAX),
+            Err(EvmError::OutOfGas)
+        );
+
+        // A huge exponent is free next to an empty base and modulus before
+        // Berlin, and returns nothing.
+        let mut input = vec![0u8; 96];
+        input[32..64].fill(0xff);
+        assert_eq!(
+            modexp(SpecId::Byzantium, &input, 0),
+            Ok(PrecompileOutput::new(0, Vec::new()))
+        );
+
+        // Osaka rejects any operand over 1024 bytes outright (EIP-7823).
+        let mut input = vec![0u8; 96];
+        input[62] = 0x04;
+        input[63] = 0x01;
+        assert!(matches!(
+            modexp(SpecId::Osaka, &input, u64::MAX),
+            Err(EvmError::PrecompileFailure(_))
+        ));
+    }
+
+    #[test]
+    fn test_big_uint_rem_matches_u512() {
+        let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..200 {
+            let a = U512([
+                next(),
+                next(),
+                next(),
+                next(),
+                next(),
+                next(),
+                next(),
+                next(),
+            ]);
+            let divisor_limbs = (next() % 8 + 1) as usize;
+            let mut limbs = [0u64; 8];
+            for limb in &mut limbs[..divisor_limbs] {
+                *limb = next() >> (next() % 64);
+            }
+            let m = U512(limbs);
+            if m.is_zero() {
+                continue;
+            }
+
+            let big = |value: U512| BigUint::from_be_bytes(&value.to_big_endian());
+            assert_eq!(big(a).rem(&big(m)), big(a % m));
+        }
+    }
+
+    #[test]
+    fn test_big_uint_pow_mod() {
+        let big = |value: u64| BigUint::from_be_bytes(&value.to_be_bytes());
+        assert_eq!(big(4).pow_mod(&[13], &big(497)), big(445));
+        assert_eq!(big(7).pow_mod(&[], &big(1)), big(0));
+        assert_eq!(big(7).pow_mod(&[0], &big(10)), big(1));
+        assert_eq!(big(7).pow_mod(&[2], &big(0)), big(0));
+        assert_eq!(big(0x1234).to_be_bytes(4), vec![0, 0, 0x12, 0x34]);
+    }
+}
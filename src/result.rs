@@ -0,0 +1,123 @@
+use crate::error::EvmError;
+use crate::log::Log;
+use primitive_types::U256;
+
+/// Selector of Solidity's `Error(string)`, used by `require` and `revert`.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Outcome of executing a piece of bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionResult {
+    /// Execution stopped normally via STOP, RETURN or by running off the end of the code.
+    Success {
+        output: Vec<u8>,
+        /// Gas spent, less the refund.
+        gas_used: u64,
+        /// Refund paid back for clearing storage, capped at a fifth of the
+        /// gas spent (EIP-3529).
+        gas_refunded: u64,
+        logs: Vec<Log>,
+    },
+    /// Execution was reverted by REVERT; unused gas is not consumed.
+    Revert { output: Vec<u8>, gas_used: u64 },
+    /// Execution hit an exceptional condition and consumed all gas.
+    Halt { reason: EvmError },
+}
+
+impl ExecutionResult {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success { .. })
+    }
+
+    pub fn is_revert(&self) -> bool {
+        matches!(self, Self::Revert { .. })
+    }
+
+    pub fn is_halt(&self) -> bool {
+        matches!(self, Self::Halt { .. })
+    }
+
+    /// Events emitted by a successful execution; reverted and halted
+    /// executions never emit any.
+    pub fn logs(&self) -> &[Log] {
+        match self {
+            Self::Success { logs, .. } => logs,
+            Self::Revert { .. } | Self::Halt { .. } => &[],
+        }
+    }
+
+    /// Gas refunded to a successful execution.
+    pub fn gas_refunded(&self) -> u64 {
+        match self {
+            Self::Success { gas_refunded, .. } => *gas_refunded,
+            Self::Revert { .. } | Self::Halt { .. } => 0,
+        }
+    }
+
+    /// Returned or reverted data; empty for an exceptional halt.
+    pub fn output(&self) -> &[u8] {
+        match self {
+            Self::Success { output, .. } | Self::Revert { output, .. } => output,
+            Self::Halt { .. } => &[],
+        }
+    }
+
+    /// Decodes the message of a Solidity `Error(string)` revert.
+    ///
+    /// Custom errors and `Panic(uint256)` are left to the caller, who can
+    /// match on the selector in [`ExecutionResult::output`].
+    pub fn revert_reason(&self) -> Option<String> {
+        let Self::Revert { output, .. } = self else {
+            return None;
+        };
+        let data = output.strip_prefix(&ERROR_SELECTOR)?;
+
+        let offset = read_usize(data, 0)?;
+        let len = read_usize(data, offset)?;
+        let start = offset.checked_add(32)?;
+        let message = data.get(start..start.checked_add(len)?)?;
+
+        String::from_utf8(message.to_vec()).ok()
+    }
+}
+
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+    let word = U256::from_big_endian(data.get(offset..offset.checked_add(32)?)?);
+    if word > U256::from(usize::MAX) {
+        return None;
+    }
+    Some(word.as_usize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    #[test]
+    fn test_revert_reason() {
+        // abi.encodeWithSignature("Error(string)", "nope")
+        let output = hex_to_bytes(concat!(
+            "0x08c379a0",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000004",
+            "6e6f706500000000000000000000000000000000000000000000000000000000",
+        ))
+        .unwrap();
+        let result = ExecutionResult::Revert {
+            output,
+            gas_used: 0,
+        };
+        assert_eq!(result.revert_reason().as_deref(), Some("nope"));
+    }
+
+    #[test]
+    fn test_revert_reason_custom_error() {
+        let result = ExecutionResult::Revert {
+            output: vec![0xde, 0xad, 0xbe, 0xef],
+            gas_used: 0,
+        };
+        assert_eq!(result.revert_reason(), None);
+        assert_eq!(result.output(), &[0xde, 0xad, 0xbe, 0xef]);
+    }
+}
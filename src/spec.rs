@@ -0,0 +1,89 @@
+use crate::opcodes;
+
+/// Ethereum hard forks, in activation order. Each one selects the opcodes
+/// that exist and the gas schedule they are priced by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpecId {
+    Frontier,
+    Homestead,
+    /// EIP-150: repriced state access and the 63/64 call gas rule.
+    Tangerine,
+    /// EIP-158/161: empty accounts; EIP-170: the contract size limit.
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    /// Constantinople without EIP-1283 net gas metering.
+    Petersburg,
+    Istanbul,
+    /// EIP-2929: warm and cold state access.
+    Berlin,
+    /// EIP-3529: smaller refunds.
+    London,
+    /// The Merge: DIFFICULTY became PREVRANDAO.
+    Merge,
+    Shanghai,
+    Cancun,
+    #[default]
+    Prague,
+    Osaka,
+}
+
+impl SpecId {
+    /// Whether `opcode` exists in this fork. Opcodes that were never
+    /// assigned are rejected by the interpreter regardless.
+    pub fn is_opcode_enabled(self, opcode: u8) -> bool {
+        let introduced = match opcode {
+            opcodes::DELEGATECALL => Self::Homestead,
+            opcodes::RETURNDATASIZE
+            | opcodes::RETURNDATACOPY
+            | opcodes::STATICCALL
+            | opcodes::REVERT => Self::Byzantium,
+            opcodes::SHL
+            | opcodes::SHR
+            | opcodes::SAR
+            | opcodes::EXTCODEHASH
+            | opcodes::CREATE2 => Self::Constantinople,
+            opcodes::CHAINID | opcodes::SELFBALANCE => Self::Istanbul,
+            opcodes::BASEFEE => Self::London,
+            opcodes::PUSH0 => Self::Shanghai,
+            opcodes::TLOAD
+            | opcodes::TSTORE
+            | opcodes::MCOPY
+            | opcodes::BLOBHASH
+            | opcodes::BLOBBASEFEE => Self::Cancun,
+            _ => Self::Frontier,
+        };
+        self >= introduced
+    }
+
+    /// Whether SSTORE is priced by how the slot changed over the whole
+    /// transaction (EIP-1283 in Constantinople, EIP-2200 from Istanbul).
+    pub fn has_net_gas_metering(self) -> bool {
+        self == Self::Constantinople || self >= Self::Istanbul
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opcode_availability() {
+        assert!(!SpecId::Merge.is_opcode_enabled(opcodes::PUSH0));
+        assert!(SpecId::Shanghai.is_opcode_enabled(opcodes::PUSH0));
+        assert!(!SpecId::Byzantium.is_opcode_enabled(opcodes::SHL));
+        assert!(SpecId::Constantinople.is_opcode_enabled(opcodes::SAR));
+        assert!(!SpecId::Frontier.is_opcode_enabled(opcodes::DELEGATECALL));
+        assert!(!SpecId::Shanghai.is_opcode_enabled(opcodes::TSTORE));
+        assert!(SpecId::Frontier.is_opcode_enabled(opcodes::ADD));
+    }
+
+    #[test]
+    fn test_net_gas_metering() {
+        assert!(!SpecId::Byzantium.has_net_gas_metering());
+        assert!(SpecId::Constantinople.has_net_gas_metering());
+        assert!(!SpecId::Petersburg.has_net_gas_metering());
+        assert!(SpecId::Istanbul.has_net_gas_metering());
+        assert!(SpecId::Prague.has_net_gas_metering());
+    }
+}
@@ -0,0 +1,555 @@
+use crate::context::Address;
+use crate::journal::{Checkpoint, EndedTransaction, JournalEntry};
+use crate::log::Log;
+use crate::storage::{Storage, TransientStorage};
+use crate::utils::keccak256;
+use primitive_types::{H256, U256};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Account {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Vec<u8>,
+    pub storage: Storage,
+}
+
+impl Account {
+    pub fn code_hash(&self) -> H256 {
+        H256::from(keccak256(&self.code))
+    }
+
+    /// An account with no code, zero nonce and zero balance (EIP-161).
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_zero() && self.nonce == 0 && self.code.is_empty()
+    }
+}
+
+/// Every account known to the EVM, keyed by address, together with the
+/// transient storage, accessed accounts and slots (EIP-2929), gas refund
+/// counter, logs, and created and self-destructed contracts of the current
+/// transaction.
+///
+/// Changes made through the setters are journaled, so they can be undone
+/// with [`WorldState::revert_to`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldState {
+    accounts: HashMap<Address, Account>,
+    transient: TransientStorage,
+    accessed_addresses: HashSet<Address>,
+    accessed_slots: HashSet<(Address, U256)>,
+    /// Signed, as undoing an earlier refund may briefly take it below zero.
+    refund: i64,
+    logs: Vec<Log>,
+    created: HashSet<Address>,
+    destroyed: HashSet<Address>,
+    journal: Vec<JournalEntry>,
+    /// How many times the journal was committed, which tells checkpoints
+    /// into the discarded journal apart from current ones.
+    commits: u64,
+}
+
+impl WorldState {
+    pub fn new() -> Self {
+        Self {
+            accounts: HashMap::new(),
+            transient: TransientStorage::new(),
+            accessed_addresses: HashSet::new(),
+            accessed_slots: HashSet::new(),
+            refund: 0,
+            logs: Vec::new(),
+            created: HashSet::new(),
+            destroyed: HashSet::new(),
+            journal: Vec::new(),
+            commits: 0,
+        }
+    }
+
+    /// Marks the current state so it can later be restored, including
+    /// across the end of a transaction.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            journal_len: self.journal.len(),
+            logs_len: self.logs.len(),
+            commits: self.commits,
+        }
+    }
+
+    /// Undoes every change made since `checkpoint` was taken. Returns
+    /// `false`, changing nothing, if the journal was committed since, as
+    /// those changes are permanent.
+    pub fn revert_to(&mut self, checkpoint: Checkpoint) -> bool {
+        if checkpoint.commits != self.commits {
+            return false;
+        }
+        let start = checkpoint.journal_len.min(self.journal.len());
+        for entry in self.journal.split_off(start).into_iter().rev() {
+            match entry {
+                JournalEntry::AccountCreated { address } => {
+                    self.accounts.remove(&address);
+                }
+                JournalEntry::AccountReplaced { address, previous } => {
+                    self.accounts.insert(address, previous);
+                }
+                JournalEntry::BalanceChanged { address, previous } => {
+                    self.account_entry(address).balance = previous;
+                }
+                JournalEntry::NonceChanged { address, previous } => {
+                    self.account_entry(address).nonce = previous;
+                }
+                JournalEntry::CodeChanged { address, previous } => {
+                    self.account_entry(address).code = previous;
+                }
+                JournalEntry::StorageChanged {
+                    address,
+                    key,
+                    previous,
+                } => {
+                    self.account_entry(address).storage.write(key, previous);
+                }
+                JournalEntry::TransientStorageChanged {
+                    address,
+                    key,
+                    previous,
+                } => {
+                    self.transient.write(address, key, previous);
+                }
+                JournalEntry::AddressWarmed { address } => {
+                    self.accessed_addresses.remove(&address);
+                }
+                JournalEntry::SlotWarmed { address, key } => {
+                    self.accessed_slots.remove(&(address, key));
+                }
+                JournalEntry::RefundChanged { previous } => {
+                    self.refund = previous;
+                }
+                JournalEntry::ContractCreated { address } => {
+                    self.created.remove(&address);
+                }
+                JournalEntry::AccountDestroyed { address } => {
+                    self.destroyed.remove(&address);
+                }
+                JournalEntry::AccountDeleted { address, previous } => {
+                    self.accounts.insert(address, previous);
+                }
+                JournalEntry::TransactionEnded(ended) => {
+                    let ended = *ended;
+                    self.created = ended.created;
+                    self.destroyed = ended.destroyed;
+                    self.transient = ended.transient;
+                    self.accessed_addresses = ended.accessed_addresses;
+                    self.accessed_slots = ended.accessed_slots;
+                    self.refund = ended.refund;
+                    for (address, original) in ended.originals {
+                        self.account_entry(address)
+                            .storage
+                            .restore_originals(original);
+                    }
+                }
+            }
+        }
+        self.logs.truncate(checkpoint.logs_len);
+        true
+    }
+
+    /// Makes every change so far permanent by discarding the journal.
+    /// Checkpoints taken before this can no longer be reverted to. Nothing
+    /// else empties the journal, so it keeps growing across transactions
+    /// until this is called.
+    pub fn commit(&mut self) {
+        self.journal.clear();
+        self.commits += 1;
+    }
+
+    /// Events emitted since the logs were last cleared.
+    pub fn logs(&self) -> &[Log] {
+        &self.logs
+    }
+
+    pub fn add_log(&mut self, log: Log) {
+        self.logs.push(log);
+    }
+
+    pub(crate) fn clear_logs(&mut self) {
+        self.logs.clear();
+    }
+
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// Returns the account at `address`, creating an empty one if needed.
+    /// Only the creation is journaled, not changes made through the
+    /// returned reference.
+    pub fn account_mut(&mut self, address: Address) -> &mut Account {
+        if !self.accounts.contains_key(&address) {
+            self.journal.push(JournalEntry::AccountCreated { address });
+        }
+        self.account_entry(address)
+    }
+
+    fn account_entry(&mut self, address: Address) -> &mut Account {
+        self.accounts.entry(address).or_default()
+    }
+
+    pub fn insert_account(&mut self, address: Address, account: Account) {
+        let entry = match self.accounts.insert(address, account) {
+            Some(previous) => JournalEntry::AccountReplaced { address, previous },
+            None => JournalEntry::AccountCreated { address },
+        };
+        self.journal.push(entry);
+    }
+
+    pub fn exists(&self, address: &Address) -> bool {
+        self.accounts.contains_key(address)
+    }
+
+    pub fn balance(&self, address: &Address) -> U256 {
+        self.account(address)
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    }
+
+    pub fn set_balance(&mut self, address: Address, balance: U256) {
+        let previous = std::mem::replace(&mut self.account_mut(address).balance, balance);
+        self.journal
+            .push(JournalEntry::BalanceChanged { address, previous });
+    }
+
+    pub fn nonce(&self, address: &Address) -> u64 {
+        self.account(address)
+            .map(|account| account.nonce)
+            .unwrap_or_default()
+    }
+
+    pub fn set_nonce(&mut self, address: Address, nonce: u64) {
+        let previous = std::mem::replace(&mut self.account_mut(address).nonce, nonce);
+        self.journal
+            .push(JournalEntry::NonceChanged { address, previous });
+    }
+
+    pub fn code(&self, address: &Address) -> &[u8] {
+        self.account(address)
+            .map(|account| account.code.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn set_code(&mut self, address: Address, code: Vec<u8>) {
+        let previous = std::mem::replace(&mut self.account_mut(address).code, code);
+        self.journal
+            .push(JournalEntry::CodeChanged { address, previous });
+    }
+
+    /// Hash of the code at `address`, or zero for a non-existent or empty
+    /// account (EIP-1052).
+    pub fn code_hash(&self, address: &Address) -> H256 {
+        match self.account(address) {
+            Some(account) if !account.is_empty() => account.code_hash(),
+            _ => H256::zero(),
+        }
+    }
+
+    pub fn sload(&self, address: &Address, key: &U256) -> U256 {
+        self.account(address)
+            .map(|account| account.storage.read(key))
+            .unwrap_or_default()
+    }
+
+    pub fn sstore(&mut self, address: Address, key: U256, value: U256) {
+        let previous = self.account_mut(address).storage.write(key, value);
+        self.journal.push(JournalEntry::StorageChanged {
+            address,
+            key,
+            previous,
+        });
+    }
+
+    /// Value of the slot `key` of `address` when the transaction started.
+    pub fn original_storage(&self, address: &Address, key: &U256) -> U256 {
+        self.account(address)
+            .map(|account| account.storage.original(key))
+            .unwrap_or_default()
+    }
+
+    pub fn tload(&self, address: &Address, key: &U256) -> U256 {
+        self.transient.read(address, key)
+    }
+
+    pub fn tstore(&mut self, address: Address, key: U256, value: U256) {
+        let previous = self.transient.write(address, key, value);
+        self.journal.push(JournalEntry::TransientStorageChanged {
+            address,
+            key,
+            previous,
+        });
+    }
+
+    /// Marks `address` as accessed, returning whether it was still cold.
+    pub fn warm_address(&mut self, address: Address) -> bool {
+        let is_cold = self.accessed_addresses.insert(address);
+        if is_cold {
+            self.journal.push(JournalEntry::AddressWarmed { address });
+        }
+        is_cold
+    }
+
+    /// Marks the slot `key` of `address` as accessed, returning whether it
+    /// was still cold.
+    pub fn warm_slot(&mut self, address: Address, key: U256) -> bool {
+        let is_cold = self.accessed_slots.insert((address, key));
+        if is_cold {
+            self.journal.push(JournalEntry::SlotWarmed { address, key });
+        }
+        is_cold
+    }
+
+    pub fn is_warm_address(&self, address: &Address) -> bool {
+        self.accessed_addresses.contains(address)
+    }
+
+    pub fn is_warm_slot(&self, address: &Address, key: &U256) -> bool {
+        self.accessed_slots.contains(&(*address, *key))
+    }
+
+    /// Gas refund earned so far in the transaction, before the EIP-3529 cap.
+    pub fn refund(&self) -> u64 {
+        self.refund.max(0) as u64
+    }
+
+    pub fn record_refund(&mut self, delta: i64) {
+        if delta != 0 {
+            let previous = self.refund;
+            self.refund += delta;
+            self.journal.push(JournalEntry::RefundChanged { previous });
+        }
+    }
+
+    /// Records that a contract was deployed at `address` in this
+    /// transaction.
+    pub fn mark_created(&mut self, address: Address) {
+        if self.created.insert(address) {
+            self.journal.push(JournalEntry::ContractCreated { address });
+        }
+    }
+
+    /// Whether the contract at `address` was deployed in this transaction.
+    pub fn is_created(&self, address: &Address) -> bool {
+        self.created.contains(address)
+    }
+
+    /// Schedules the account at `address` for deletion at the end of the
+    /// transaction, returning whether it was not scheduled already.
+    pub fn mark_destroyed(&mut self, address: Address) -> bool {
+        let is_new = self.destroyed.insert(address);
+        if is_new {
+            self.journal
+                .push(JournalEntry::AccountDestroyed { address });
+        }
+        is_new
+    }
+
+    pub fn is_destroyed(&self, address: &Address) -> bool {
+        self.destroyed.contains(address)
+    }
+
+    /// Deletes the accounts that self-destructed, drops transient storage,
+    /// the refund counter and the record of which accounts and slots were
+    /// accessed, and makes the current storage the original storage of the
+    /// next transaction. All of it is journaled, so a checkpoint taken
+    /// before the transaction still restores the state it was taken in.
+    pub fn end_transaction(&mut self) {
+        let destroyed = std::mem::take(&mut self.destroyed);
+        for address in &destroyed {
+            if let Some(previous) = self.accounts.remove(address) {
+                self.journal.push(JournalEntry::AccountDeleted {
+                    address: *address,
+                    previous,
+                });
+            }
+        }
+        let originals = self
+            .accounts
+            .iter_mut()
+            .map(|(address, account)| (*address, account.storage.commit_originals()))
+            .filter(|(_, original)| !original.is_empty())
+            .collect();
+        self.journal
+            .push(JournalEntry::TransactionEnded(Box::new(EndedTransaction {
+                created: std::mem::take(&mut self.created),
+                destroyed,
+                transient: std::mem::take(&mut self.transient),
+                accessed_addresses: std::mem::take(&mut self.accessed_addresses),
+                accessed_slots: std::mem::take(&mut self.accessed_slots),
+                refund: std::mem::take(&mut self.refund),
+                originals,
+            })));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_is_per_account() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+        let b = Address::repeat_byte(0x0b);
+
+        state.sstore(a, U256::one(), U256::from(100));
+        assert_eq!(state.sload(&a, &U256::one()), U256::from(100));
+        assert_eq!(state.sload(&b, &U256::one()), U256::zero());
+        assert!(!state.exists(&b));
+    }
+
+    #[test]
+    fn test_revert_to_checkpoint() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+        let b = Address::repeat_byte(0x0b);
+        state.set_balance(a, U256::from(10));
+        state.sstore(a, U256::one(), U256::from(1));
+        let before = state.clone();
+
+        let checkpoint = state.checkpoint();
+        state.set_balance(a, U256::from(7));
+        state.set_nonce(a, 3);
+        state.sstore(a, U256::one(), U256::from(2));
+        state.sstore(a, U256::one(), U256::from(3));
+        state.set_code(b, vec![0x00]);
+        state.add_log(Log {
+            address: a,
+            topics: Vec::new(),
+            data: Vec::new(),
+        });
+
+        state.revert_to(checkpoint);
+        assert_eq!(state.accounts, before.accounts);
+        assert!(!state.exists(&b));
+        assert!(state.logs().is_empty());
+    }
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+
+        let outer = state.checkpoint();
+        state.set_balance(a, U256::from(1));
+        let inner = state.checkpoint();
+        state.set_balance(a, U256::from(2));
+
+        state.revert_to(inner);
+        assert_eq!(state.balance(&a), U256::from(1));
+        state.revert_to(outer);
+        assert!(!state.exists(&a));
+    }
+
+    #[test]
+    fn test_commit_discards_journal() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+
+        let checkpoint = state.checkpoint();
+        state.set_balance(a, U256::from(1));
+        state.commit();
+        assert!(!state.revert_to(checkpoint));
+        assert_eq!(state.balance(&a), U256::from(1));
+
+        // A stale checkpoint doesn't reach into the new journal either.
+        state.set_balance(a, U256::from(2));
+        assert!(!state.revert_to(checkpoint));
+        assert_eq!(state.balance(&a), U256::from(2));
+    }
+
+    #[test]
+    fn test_warming_is_reverted() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+
+        assert!(state.warm_address(a));
+        let checkpoint = state.checkpoint();
+        assert!(!state.warm_address(a));
+        assert!(state.warm_slot(a, U256::one()));
+        assert!(state.is_warm_slot(&a, &U256::one()));
+
+        state.revert_to(checkpoint);
+        assert!(state.is_warm_address(&a));
+        assert!(!state.is_warm_slot(&a, &U256::one()));
+
+        state.end_transaction();
+        assert!(!state.is_warm_address(&a));
+    }
+
+    #[test]
+    fn test_destroyed_accounts_deleted_at_end_of_transaction() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+        state.set_code(a, vec![0x00]);
+
+        let checkpoint = state.checkpoint();
+        assert!(state.mark_destroyed(a));
+        assert!(!state.mark_destroyed(a));
+        state.revert_to(checkpoint);
+        assert!(!state.is_destroyed(&a));
+
+        state.mark_created(a);
+        state.mark_destroyed(a);
+        // The account lives on until the transaction is over.
+        assert!(state.exists(&a));
+        state.end_transaction();
+        assert!(!state.exists(&a));
+        assert!(!state.is_created(&a));
+    }
+
+    #[test]
+    fn test_end_transaction_is_reverted() {
+        let mut state = WorldState::new();
+        let a = Address::repeat_byte(0x0a);
+        let b = Address::repeat_byte(0x0b);
+        state.set_code(a, vec![0x00]);
+        state.sstore(b, U256::one(), U256::from(1));
+        state.end_transaction();
+
+        let checkpoint = state.checkpoint();
+        state.sstore(b, U256::one(), U256::from(2));
+        state.tstore(b, U256::one(), U256::from(3));
+        state.warm_slot(b, U256::one());
+        state.record_refund(100);
+        state.mark_created(a);
+        state.mark_destroyed(a);
+        let before = state.clone();
+
+        state.end_transaction();
+        assert!(!state.exists(&a));
+        assert_eq!(state.original_storage(&b, &U256::one()), U256::from(2));
+
+        // Back inside the transaction, with everything it had accumulated.
+        let mut reopened = state.clone();
+        assert!(reopened.revert_to(before.checkpoint()));
+        assert_eq!(reopened, before);
+        assert_eq!(reopened.tload(&b, &U256::one()), U256::from(3));
+        assert_eq!(reopened.original_storage(&b, &U256::one()), U256::from(1));
+
+        // And back to before it started.
+        assert!(state.revert_to(checkpoint));
+        assert_eq!(state.sload(&b, &U256::one()), U256::from(1));
+        assert_eq!(state.code(&a), [0x00]);
+        assert!(!state.is_created(&a));
+    }
+
+    #[test]
+    fn test_code_hash() {
+        let mut state = WorldState::new();
+        let missing = Address::repeat_byte(0x01);
+        let funded = Address::repeat_byte(0x02);
+        let contract = Address::repeat_byte(0x03);
+
+        state.set_balance(funded, U256::one());
+        state.set_code(contract, vec![0x00]);
+
+        assert_eq!(state.code_hash(&missing), H256::zero());
+        assert_eq!(state.code_hash(&funded), H256::from(keccak256(&[])));
+        assert_eq!(state.code_hash(&contract), H256::from(keccak256(&[0x00])));
+    }
+}
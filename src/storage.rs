@@ -1,25 +1,84 @@
+use crate::context::Address;
 use primitive_types::U256;
 use std::collections::HashMap;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Storage {
     data: HashMap<U256, U256>,
+    /// Values of the slots written in the current transaction, as they were
+    /// when it started.
+    original: HashMap<U256, U256>,
 }
 
 impl Storage {
     pub fn new() -> Self {
         Self {
             data: HashMap::new(),
+            original: HashMap::new(),
         }
     }
 
-    pub fn write(&mut self, key: U256, value: U256) {
-        self.data.insert(key, value);
+    /// Stores `value` under `key`, returning the value it replaces.
+    pub fn write(&mut self, key: U256, value: U256) -> U256 {
+        let previous = self.data.insert(key, value).unwrap_or_default();
+        self.original.entry(key).or_insert(previous);
+        previous
     }
 
     pub fn read(&self, key: &U256) -> U256 {
         self.data.get(key).copied().unwrap_or(U256::zero())
     }
+
+    /// Value of `key` at the start of the current transaction.
+    pub fn original(&self, key: &U256) -> U256 {
+        self.original
+            .get(key)
+            .copied()
+            .unwrap_or_else(|| self.read(key))
+    }
+
+    /// Makes the current values the originals for the next transaction,
+    /// returning the originals of the one that ended.
+    pub fn commit_originals(&mut self) -> HashMap<U256, U256> {
+        std::mem::take(&mut self.original)
+    }
+
+    /// Puts back originals returned by [`Storage::commit_originals`].
+    pub fn restore_originals(&mut self, original: HashMap<U256, U256>) {
+        self.original = original;
+    }
+}
+
+/// Storage that only lives for the duration of a transaction (EIP-1153),
+/// keyed by the owning address and slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransientStorage {
+    data: HashMap<(Address, U256), U256>,
+}
+
+impl TransientStorage {
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    /// Stores `value` under `key` for `address`, returning the value it
+    /// replaces.
+    pub fn write(&mut self, address: Address, key: U256, value: U256) -> U256 {
+        self.data.insert((address, key), value).unwrap_or_default()
+    }
+
+    pub fn read(&self, address: &Address, key: &U256) -> U256 {
+        self.data
+            .get(&(*address, *key))
+            .copied()
+            .unwrap_or(U256::zero())
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
 }
 
 #[cfg(test)]
@@ -33,6 +92,43 @@ fn test_storage_write_read() {
         assert_eq!(storage.read(&U256::from(1)), U256::from(100));
     }
 
+    #[test]
+    fn test_transient_storage_scoped_to_address() {
+        let mut storage = TransientStorage::new();
+        let key = U256::from(1);
+        storage.write(Address::repeat_byte(0x01), key, U256::from(100));
+        assert_eq!(
+            storage.read(&Address::repeat_byte(0x01), &key),
+            U256::from(100)
+        );
+        assert_eq!(
+            storage.read(&Address::repeat_byte(0x02), &key),
+            U256::zero()
+        );
+
+        storage.clear();
+        assert_eq!(
+            storage.read(&Address::repeat_byte(0x01), &key),
+            U256::zero()
+        );
+    }
+
+    #[test]
+    fn test_storage_original() {
+        let mut storage = Storage::new();
+        let key = U256::from(1);
+        storage.write(key, U256::from(100));
+        storage.commit_originals();
+
+        storage.write(key, U256::from(200));
+        storage.write(key, U256::from(300));
+        assert_eq!(storage.original(&key), U256::from(100));
+        assert_eq!(storage.original(&U256::from(2)), U256::zero());
+
+        storage.commit_originals();
+        assert_eq!(storage.original(&key), U256::from(300));
+    }
+
     #[test]
     fn test_storage_default_zero() {
         let storage = Storage::new();
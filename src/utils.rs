@@ -1,4 +1,6 @@
+use crate::context::Address;
 use crate::error::{EvmError, Result};
+use primitive_types::{H256, U256};
 
 pub fn hex_to_bytes(input: &str) -> Result<Vec<u8>> {
     let input = input.strip_prefix("0x").unwrap_or(input);
@@ -14,6 +16,332 @@ pub fn bytes_to_hex(bytes: &[u8]) -> String {
     format!("0x{}", hex::encode(bytes))
 }
 
+pub fn address_to_u256(address: Address) -> U256 {
+    U256::from_big_endian(address.as_bytes())
+}
+
+/// Truncates a stack word to its low 20 bytes.
+pub fn u256_to_address(value: U256) -> Address {
+    Address::from_slice(&value.to_big_endian()[12..])
+}
+
+/// Returns `data[offset..offset + size]`, padded with zeros where the range
+/// runs past the end of `data`.
+pub fn slice_padded(data: &[u8], offset: U256, size: usize) -> Vec<u8> {
+    let mut out = vec![0u8; size];
+    if offset < U256::from(data.len()) {
+        let offset = offset.as_usize();
+        let available = (data.len() - offset).min(size);
+        out[..available].copy_from_slice(&data[offset..offset + available]);
+    }
+    out
+}
+
+/// Address of a contract created with CREATE: the last 20 bytes of
+/// `keccak256(rlp([sender, nonce]))`.
+pub fn create_address(sender: Address, nonce: u64) -> Address {
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce.leading_zeros() as usize / 8..];
+
+    // Both items are short, so every RLP header is a single byte.
+    let mut rlp = vec![0xc0, 0x80 + 20];
+    rlp.extend_from_slice(sender.as_bytes());
+    match nonce_bytes {
+        [byte] if *byte < 0x80 => rlp.push(*byte),
+        _ => {
+            rlp.push(0x80 + nonce_bytes.len() as u8);
+            rlp.extend_from_slice(nonce_bytes);
+        }
+    }
+    rlp[0] += (rlp.len() - 1) as u8;
+
+    Address::from_slice(&keccak256(&rlp)[12..])
+}
+
+/// Address of a contract created with CREATE2 (EIP-1014): the last 20 bytes
+/// of `keccak256(0xff ++ sender ++ salt ++ keccak256(init_code))`.
+pub fn create2_address(sender: Address, salt: U256, init_code_hash: H256) -> Address {
+    let mut preimage = Vec::with_capacity(85);
+    preimage.push(0xff);
+    preimage.extend_from_slice(sender.as_bytes());
+    preimage.extend_from_slice(&salt.to_big_endian());
+    preimage.extend_from_slice(init_code_hash.as_bytes());
+
+    Address::from_slice(&keccak256(&preimage)[12..])
+}
+
+const KECCAK_RATE: usize = 136;
+
+const KECCAK_ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+const KECCAK_RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+const KECCAK_PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in KECCAK_ROUND_CONSTANTS {
+        // Theta
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[y * 5 + x] ^= d;
+            }
+        }
+
+        // Rho and Pi
+        let mut last = state[1];
+        for (&pi, &rho) in KECCAK_PI.iter().zip(KECCAK_RHO.iter()) {
+            let current = state[pi];
+            state[pi] = last.rotate_left(rho);
+            last = current;
+        }
+
+        // Chi
+        for y in 0..5 {
+            let mut row = [0u64; 5];
+            row.copy_from_slice(&state[y * 5..y * 5 + 5]);
+            for x in 0..5 {
+                state[y * 5 + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        state[0] ^= round_constant;
+    }
+}
+
+/// Keccak-256 as used by Ethereum (the original Keccak padding, not SHA3-256).
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(KECCAK_RATE) * KECCAK_RATE, 0);
+    let last = padded.len() - 1;
+    padded[last] |= 0x80;
+
+    for block in padded.chunks_exact(KECCAK_RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("chunk is 8 bytes"));
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut output = [0u8; 32];
+    for (bytes, lane) in output.chunks_exact_mut(8).zip(state.iter()) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    output
+}
+
+const SHA256_ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Appends the Merkle-Damgard padding shared by SHA-256 and RIPEMD-160: a
+/// 1 bit, zeros, and the message length in bits as a 64-bit integer.
+fn pad_message(data: &[u8], length_to_bytes: fn(u64) -> [u8; 8]) -> Vec<u8> {
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    padded.resize((padded.len() + 8).div_ceil(64) * 64 - 8, 0);
+    padded.extend_from_slice(&length_to_bytes(data.len() as u64 * 8));
+    padded
+}
+
+/// SHA-256 as specified in FIPS 180-4.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hash: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    for block in pad_message(data, u64::to_be_bytes).chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().expect("chunk is 4 bytes"));
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+        for (&k, &word) in SHA256_ROUND_CONSTANTS.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut output = [0u8; 32];
+    for (bytes, word) in output.chunks_exact_mut(4).zip(hash.iter()) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    output
+}
+
+/// Message word used by each step of the left and right RIPEMD-160 lines.
+const RIPEMD160_WORDS: [[usize; 80]; 2] = [
+    [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9,
+        5, 2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8,
+        12, 4, 13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+    ],
+    [
+        5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8,
+        12, 4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11,
+        15, 0, 5, 12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+    ],
+];
+
+/// Left rotation applied by each step of the left and right lines.
+const RIPEMD160_SHIFTS: [[u32; 80]; 2] = [
+    [
+        11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12,
+        15, 9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14,
+        15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11,
+        8, 5, 6,
+    ],
+    [
+        8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7,
+        12, 7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11,
+        14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13,
+        11, 11,
+    ],
+];
+
+/// Per-round additive constants of the left and right lines.
+const RIPEMD160_CONSTANTS: [[u32; 5]; 2] = [
+    [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e],
+    [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000],
+];
+
+/// The five boolean functions of RIPEMD-160, one per round.
+fn ripemd160_f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// RIPEMD-160, the 20-byte hash used by Bitcoin addresses.
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    let mut hash: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+    for block in pad_message(data, u64::to_le_bytes).chunks_exact(64) {
+        let mut x = [0u32; 16];
+        for (word, bytes) in x.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().expect("chunk is 4 bytes"));
+        }
+
+        // Both lines start from the current hash; the right one walks the
+        // boolean functions in reverse.
+        let mut lines = [hash; 2];
+        for (line, [a, b, c, d, e]) in lines.iter_mut().enumerate() {
+            for step in 0..80 {
+                let round = step / 16;
+                let f = if line == 0 {
+                    ripemd160_f(round, *b, *c, *d)
+                } else {
+                    ripemd160_f(4 - round, *b, *c, *d)
+                };
+                let t = a
+                    .wrapping_add(f)
+                    .wrapping_add(x[RIPEMD160_WORDS[line][step]])
+                    .wrapping_add(RIPEMD160_CONSTANTS[line][round])
+                    .rotate_left(RIPEMD160_SHIFTS[line][step])
+                    .wrapping_add(*e);
+                *a = *e;
+                *e = *d;
+                *d = c.rotate_left(10);
+                *c = *b;
+                *b = t;
+            }
+        }
+
+        let [left, right] = lines;
+        hash = [
+            hash[1].wrapping_add(left[2]).wrapping_add(right[3]),
+            hash[2].wrapping_add(left[3]).wrapping_add(right[4]),
+            hash[3].wrapping_add(left[4]).wrapping_add(right[0]),
+            hash[4].wrapping_add(left[0]).wrapping_add(right[1]),
+            hash[0].wrapping_add(left[1]).wrapping_add(right[2]),
+        ];
+    }
+
+    let mut output = [0u8; 20];
+    for (bytes, word) in output.chunks_exact_mut(4).zip(hash.iter()) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,4 +356,125 @@ fn test_hex_to_bytes() {
     fn test_bytes_to_hex() {
         assert_eq!(bytes_to_hex(&[0x60, 0x01]), "0x6001");
     }
+
+    #[test]
+    fn test_address_round_trip() {
+        let address = Address::repeat_byte(0x11);
+        assert_eq!(u256_to_address(address_to_u256(address)), address);
+        assert_eq!(u256_to_address(U256::MAX), Address::repeat_byte(0xff));
+    }
+
+    #[test]
+    fn test_slice_padded() {
+        assert_eq!(slice_padded(&[1, 2, 3], U256::one(), 4), vec![2, 3, 0, 0]);
+        assert_eq!(slice_padded(&[1, 2, 3], U256::MAX, 2), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_create_address() {
+        let sender = Address::from_slice(
+            &hex_to_bytes("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap(),
+        );
+        let expected = [
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d",
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8",
+            "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91",
+        ];
+        for (nonce, expected) in expected.iter().enumerate() {
+            let address = create_address(sender, nonce as u64);
+            assert_eq!(bytes_to_hex(address.as_bytes()), *expected);
+        }
+    }
+
+    #[test]
+    fn test_create2_address() {
+        // Example 5 of EIP-1014.
+        let sender = Address::from_slice(
+            &hex_to_bytes("0x00000000000000000000000000000000deadbeef").unwrap(),
+        );
+        let salt = U256::from(0xcafebabe_u64);
+        let init_code = hex_to_bytes("0xdeadbeef").unwrap();
+        let address = create2_address(sender, salt, H256::from(keccak256(&init_code)));
+        assert_eq!(
+            bytes_to_hex(address.as_bytes()),
+            "0x60f3f640a8508fc6a86d45df051962668e1e8ac7"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_empty() {
+        assert_eq!(
+            bytes_to_hex(&keccak256(&[])),
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_abc() {
+        assert_eq!(
+            bytes_to_hex(&keccak256(b"abc")),
+            "0x4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_multiple_blocks() {
+        let hash = |len: usize| bytes_to_hex(&keccak256(&vec![0xa3; len]));
+        // One byte short of the 136-byte rate: both padding bits share a byte.
+        assert_eq!(
+            hash(135),
+            "0x3d28d08c3dacab77392064a939f3e7f8d03f2e02e2c664ac08a05f63ac652626"
+        );
+        // Exactly one block, so the padding takes a block of its own.
+        assert_eq!(
+            hash(136),
+            "0xb82d89d96e5575d11a9e1f4cabb2a45e60899e69a19a724cd796bdcf13511018"
+        );
+        // 200 times 0xa3, from the Keccak known-answer tests.
+        assert_eq!(
+            hash(200),
+            "0x3a57666b048777f2c953dc4456f45a2588e1cb6f2da760122d530ac2ce607d4a"
+        );
+        assert_eq!(
+            hash(272),
+            "0xa657381e5615f539cac71f13c74473a5e77eaa2bd8348422dcac221c8f13da94"
+        );
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            bytes_to_hex(&sha256(&[])),
+            "0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            bytes_to_hex(&sha256(b"abc")),
+            "0xba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two blocks, with the length spilling into the second.
+        assert_eq!(
+            bytes_to_hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "0x248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_ripemd160() {
+        assert_eq!(
+            bytes_to_hex(&ripemd160(&[])),
+            "0x9c1185a5c5e9fc54612808977ee8f548b2258d31"
+        );
+        assert_eq!(
+            bytes_to_hex(&ripemd160(b"abc")),
+            "0x8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(
+            bytes_to_hex(&ripemd160(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "0x12a053384a9c0c88e405a06c27dcf49ada62eb2b"
+        );
+    }
 }